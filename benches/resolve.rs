@@ -0,0 +1,80 @@
+/*
+*   Copyright (c) 2019 Lukas Krejci
+*   All rights reserved.
+
+*   Licensed under the Apache License, Version 2.0 (the "License");
+*   you may not use this file except in compliance with the License.
+*   You may obtain a copy of the License at
+
+*   http://www.apache.org/licenses/LICENSE-2.0
+
+*   Unless required by applicable law or agreed to in writing, software
+*   distributed under the License is distributed on an "AS IS" BASIS,
+*   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*   See the License for the specific language governing permissions and
+*   limitations under the License.
+*/
+
+//! Compares parsing the pointer before finding the value with the single pass `resolve_str()`.
+//!
+//! Parsing allocates the vector of steps and a string for each name in the pointer (and `find()`
+//! clones them once more), while `resolve_str()` allocates just a single string buffer.
+//!
+//! Run with `cargo bench`.
+
+mod common;
+
+use common::map;
+use serde_pointer::{resolve_str, Pointer};
+use serde_value::Value;
+use std::hint::black_box;
+use std::str::FromStr;
+use std::time::Instant;
+
+const ITERATIONS: u32 = 100_000;
+const POINTER: &str = "/servers/1/settings/network~1ports/0";
+
+fn main() {
+    let val = data();
+    assert_eq!(
+        Pointer::from_str(POINTER).unwrap().find(&val),
+        resolve_str(POINTER, &val)
+    );
+
+    bench("from_str + find", || {
+        Pointer::from_str(black_box(POINTER))
+            .ok()
+            .and_then(|p| p.find(&val).cloned())
+    });
+    bench("resolve_str", || {
+        resolve_str(black_box(POINTER), &val).cloned()
+    });
+}
+
+fn bench<F: FnMut() -> Option<Value>>(name: &str, mut f: F) {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{:20} {:>10.1?} total, {:>8.1?} per iteration",
+        name,
+        elapsed,
+        elapsed / ITERATIONS
+    );
+}
+
+fn data() -> Value {
+    let server = || {
+        map(vec![(
+            "settings",
+            map(vec![(
+                "network/ports",
+                Value::Seq(vec![Value::U16(80), Value::U16(443)]),
+            )]),
+        )])
+    };
+
+    map(vec![("servers", Value::Seq(vec![server(), server()]))])
+}
@@ -0,0 +1,82 @@
+/*
+*   Copyright (c) 2019 Lukas Krejci
+*   All rights reserved.
+
+*   Licensed under the Apache License, Version 2.0 (the "License");
+*   you may not use this file except in compliance with the License.
+*   You may obtain a copy of the License at
+
+*   http://www.apache.org/licenses/LICENSE-2.0
+
+*   Unless required by applicable law or agreed to in writing, software
+*   distributed under the License is distributed on an "AS IS" BASIS,
+*   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*   See the License for the specific language governing permissions and
+*   limitations under the License.
+*/
+
+//! Compares updating a string leaf using `set()`, which allocates a new string every time, with
+//! `set_str()`, which overwrites the existing string in place.
+//!
+//! Run with `cargo bench`.
+
+mod common;
+
+use common::map;
+use serde_pointer::Pointer;
+use serde_value::Value;
+use std::hint::black_box;
+use std::str::FromStr;
+use std::time::Instant;
+
+const ITERATIONS: u32 = 100_000;
+const POINTER: &str = "/servers/1/settings/name";
+const VALUES: [&str; 2] = ["primary-server", "secondary-server"];
+
+fn main() {
+    let pointer = Pointer::from_str(POINTER).unwrap();
+
+    let mut set = data();
+    let mut set_str = data();
+    pointer
+        .set(&mut set, Value::String(VALUES[0].to_owned()))
+        .unwrap();
+    pointer.set_str(&mut set_str, VALUES[0]).unwrap();
+    assert_eq!(set, set_str);
+
+    let mut val = data();
+    bench("set", |i| {
+        pointer
+            .set(&mut val, Value::String(black_box(VALUES[i % 2]).to_owned()))
+            .unwrap();
+    });
+    let mut val = data();
+    bench("set_str", |i| {
+        pointer.set_str(&mut val, black_box(VALUES[i % 2])).unwrap();
+    });
+}
+
+fn bench<F: FnMut(usize)>(name: &str, mut f: F) {
+    let start = Instant::now();
+    for i in 0..ITERATIONS {
+        f(i as usize);
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{:20} {:>10.1?} total, {:>8.1?} per iteration",
+        name,
+        elapsed,
+        elapsed / ITERATIONS
+    );
+}
+
+fn data() -> Value {
+    let server = || {
+        map(vec![(
+            "settings",
+            map(vec![("name", Value::String("server".to_owned()))]),
+        )])
+    };
+
+    map(vec![("servers", Value::Seq(vec![server(), server()]))])
+}
@@ -0,0 +1,139 @@
+/*
+*   Copyright (c) 2019 Lukas Krejci
+*   All rights reserved.
+
+*   Licensed under the Apache License, Version 2.0 (the "License");
+*   you may not use this file except in compliance with the License.
+*   You may obtain a copy of the License at
+
+*   http://www.apache.org/licenses/LICENSE-2.0
+
+*   Unless required by applicable law or agreed to in writing, software
+*   distributed under the License is distributed on an "AS IS" BASIS,
+*   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*   See the License for the specific language governing permissions and
+*   limitations under the License.
+*/
+
+//! The derive macro for the `serde_pointer::PointerFields` trait. Use it through the `derive`
+//! feature of `serde-pointer`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Error, Fields, LitStr};
+
+/// Implements `serde_pointer::PointerFields` for a struct with named fields, mapping the field
+/// names to the names serde serializes them under.
+///
+/// The field level `#[serde(rename = "...")]` is supported. The structs using `rename_all` or
+/// flattened fields are rejected because their pointers cannot be derived from the field names
+/// alone. The fields skipped by serde have no serde name.
+#[proc_macro_derive(PointerFields, attributes(serde))]
+pub fn derive_pointer_fields(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match _derive(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn _derive(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    if _serde_attr(&input.attrs, "rename_all")?.is_some() {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "PointerFields doesn't support #[serde(rename_all)]",
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "PointerFields can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "PointerFields can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut arms = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        if _serde_attr(&field.attrs, "flatten")?.is_some() {
+            return Err(Error::new_spanned(
+                ident,
+                "PointerFields doesn't support #[serde(flatten)]",
+            ));
+        }
+        if _serde_attr(&field.attrs, "skip")?.is_some()
+            || _serde_attr(&field.attrs, "skip_serializing")?.is_some()
+        {
+            continue;
+        }
+
+        let rust_name = ident.to_string();
+        let rust_name = rust_name
+            .strip_prefix("r#")
+            .unwrap_or(&rust_name)
+            .to_owned();
+        let serde_name = match _serde_attr(&field.attrs, "rename")? {
+            Some(Some(name)) => name.value(),
+            _ => rust_name.clone(),
+        };
+        arms.push(quote! { #rust_name => ::std::option::Option::Some(#serde_name), });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::serde_pointer::PointerFields for #name #ty_generics #where_clause {
+            fn serde_field_name(field: &str) -> ::std::option::Option<&'static str> {
+                match field {
+                    #(#arms)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+        }
+    })
+}
+
+/// Looks for the `#[serde(<name>)]` or `#[serde(<name> = "...")]` attribute. Returns the string
+/// value of the attribute, if it has one.
+fn _serde_attr(attrs: &[Attribute], name: &str) -> Result<Option<Option<LitStr>>, Error> {
+    let mut found = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident(name) {
+                if meta.input.peek(syn::token::Paren) {
+                    return Err(
+                        meta.error("PointerFields doesn't support this form of the attribute")
+                    );
+                }
+                found = Some(match meta.value() {
+                    Ok(value) => Some(value.parse::<LitStr>()?),
+                    Err(_) => None,
+                });
+            } else if let Ok(value) = meta.value() {
+                // skip the values of the attributes we're not interested in
+                value.parse::<syn::Expr>()?;
+            } else if meta.input.peek(syn::token::Paren) {
+                meta.parse_nested_meta(|nested| {
+                    if let Ok(value) = nested.value() {
+                        value.parse::<syn::Expr>()?;
+                    }
+                    Ok(())
+                })?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(found)
+}
@@ -0,0 +1,193 @@
+/*
+*   Copyright (c) 2019 Lukas Krejci
+*   All rights reserved.
+
+*   Licensed under the Apache License, Version 2.0 (the "License");
+*   you may not use this file except in compliance with the License.
+*   You may obtain a copy of the License at
+
+*   http://www.apache.org/licenses/LICENSE-2.0
+
+*   Unless required by applicable law or agreed to in writing, software
+*   distributed under the License is distributed on an "AS IS" BASIS,
+*   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*   See the License for the specific language governing permissions and
+*   limitations under the License.
+*/
+
+use crate::pointer::{Pointer, Step};
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::fmt;
+use std::fmt::Display;
+
+const TAG_NAME: u8 = 0;
+const TAG_INDEX: u8 = 1;
+const TAG_NEW_ELEMENT: u8 = 2;
+const TAG_WILDCARD: u8 = 3;
+const TAG_INDEX_FROM_END: u8 = 4;
+
+/// Describes why the bytes are not a valid compact encoding of a pointer. The parameters are the
+/// offsets of the offending bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompactError {
+    /// The data ends in the middle of a step.
+    UnexpectedEnd(usize),
+
+    /// The byte is not a known step tag.
+    InvalidTag(usize),
+
+    /// The number doesn't fit into `usize`.
+    Overflow(usize),
+
+    /// The name is not valid UTF-8.
+    InvalidUtf8(usize),
+}
+
+/// Each step is a tag byte, followed by the length and the UTF-8 bytes of a name or by an index.
+/// The lengths and the indices are LEB128 varints.
+pub(crate) fn to_bytes(pointer: &Pointer) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for step in pointer.steps() {
+        match step {
+            Step::Name(name) => {
+                bytes.push(TAG_NAME);
+                _write_varint(&mut bytes, name.len() as u64);
+                bytes.extend_from_slice(name.as_bytes());
+            }
+            Step::Index(idx) => {
+                bytes.push(TAG_INDEX);
+                _write_varint(&mut bytes, *idx as u64);
+            }
+            Step::NewElement => bytes.push(TAG_NEW_ELEMENT),
+            Step::Wildcard => bytes.push(TAG_WILDCARD),
+            Step::IndexFromEnd(idx) => {
+                bytes.push(TAG_INDEX_FROM_END);
+                _write_varint(&mut bytes, *idx as u64);
+            }
+        }
+    }
+    bytes
+}
+
+pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Pointer, CompactError> {
+    let mut steps = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let tag_pos = pos;
+        pos += 1;
+        steps.push(match bytes[tag_pos] {
+            TAG_NAME => {
+                let len_pos = pos;
+                let len = _read_varint(bytes, &mut pos)?;
+                let end = pos
+                    .checked_add(len)
+                    .filter(|end| *end <= bytes.len())
+                    .ok_or(CompactError::UnexpectedEnd(len_pos))?;
+                let name = std::str::from_utf8(&bytes[pos..end])
+                    .map_err(|e| CompactError::InvalidUtf8(pos + e.valid_up_to()))?;
+                pos = end;
+                Step::Name(name.to_owned())
+            }
+            TAG_INDEX => Step::Index(_read_varint(bytes, &mut pos)?),
+            TAG_NEW_ELEMENT => Step::NewElement,
+            TAG_WILDCARD => Step::Wildcard,
+            TAG_INDEX_FROM_END => Step::IndexFromEnd(_read_varint(bytes, &mut pos)?),
+            _ => return Err(CompactError::InvalidTag(tag_pos)),
+        });
+    }
+    Ok(Pointer::from(steps))
+}
+
+fn _write_varint(bytes: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        bytes.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    bytes.push(n as u8);
+}
+
+fn _read_varint(bytes: &[u8], pos: &mut usize) -> Result<usize, CompactError> {
+    let start = *pos;
+    let mut n: u64 = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.get(*pos).ok_or(CompactError::UnexpectedEnd(*pos))?;
+        *pos += 1;
+        if shift >= 64 || (shift == 63 && byte & 0x7f > 1) {
+            return Err(CompactError::Overflow(start));
+        }
+        n |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return usize::try_from(n).map_err(|_| CompactError::Overflow(start));
+        }
+        shift += 7;
+    }
+}
+
+impl Display for CompactError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompactError::UnexpectedEnd(pos) => f.write_fmt(format_args!(
+                "Compact JSON Pointer ends unexpectedly in the step at offset {}",
+                pos
+            )),
+            CompactError::InvalidTag(pos) => f.write_fmt(format_args!(
+                "Invalid step tag in compact JSON Pointer at offset {}",
+                pos
+            )),
+            CompactError::Overflow(pos) => f.write_fmt(format_args!(
+                "Number too large in compact JSON Pointer at offset {}",
+                pos
+            )),
+            CompactError::InvalidUtf8(pos) => f.write_fmt(format_args!(
+                "Invalid UTF-8 in compact JSON Pointer at offset {}",
+                pos
+            )),
+        }
+    }
+}
+
+impl StdError for CompactError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut pointer = Pointer::default();
+        pointer
+            .push_name("a/b~c")
+            .push(Step::Index(300))
+            .push_name("")
+            .push_name("0")
+            .push(Step::NewElement);
+
+        let bytes = to_bytes(&pointer);
+        assert_eq!(&[TAG_INDEX, 0xac, 0x02], &bytes[7..10]);
+        assert_eq!(Ok(pointer), from_bytes(&bytes));
+        assert_eq!(Ok(Pointer::default()), from_bytes(&[]));
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(Err(CompactError::InvalidTag(0)), from_bytes(&[9]));
+        assert_eq!(
+            Err(CompactError::UnexpectedEnd(1)),
+            from_bytes(&[TAG_NAME, 3, b'a'])
+        );
+        assert_eq!(
+            Err(CompactError::UnexpectedEnd(2)),
+            from_bytes(&[TAG_INDEX, 0x80])
+        );
+        assert_eq!(
+            Err(CompactError::InvalidUtf8(2)),
+            from_bytes(&[TAG_NAME, 1, 0xff])
+        );
+        assert_eq!(
+            Err(CompactError::Overflow(1)),
+            from_bytes(&[TAG_INDEX, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f])
+        );
+    }
+}
@@ -0,0 +1,138 @@
+/*
+*   Copyright (c) 2019 Lukas Krejci
+*   All rights reserved.
+
+*   Licensed under the Apache License, Version 2.0 (the "License");
+*   you may not use this file except in compliance with the License.
+*   You may obtain a copy of the License at
+
+*   http://www.apache.org/licenses/LICENSE-2.0
+
+*   Unless required by applicable law or agreed to in writing, software
+*   distributed under the License is distributed on an "AS IS" BASIS,
+*   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*   See the License for the specific language governing permissions and
+*   limitations under the License.
+*/
+
+//! The pointer semantics of `serde_json::Value::pointer()`.
+//!
+//! The lookups behave as if the value was first converted to a `serde_json::Value`, i.e. the
+//! present optional values and the newtypes are looked through and the bool and integer map keys
+//! are matched by their string form. The known differences from `Pointer::find()` are:
+//!
+//! * The invalid escapes, like `~2` or a trailing `~`, are kept as they are instead of rejecting
+//!   the pointer. Only `~1` and `~0` are unescaped, in this order.
+//! * The URI fragment form with the leading `#` is not accepted.
+//! * A segment is only interpreted as an index on a sequence. On a map, it matches the string key
+//!   or the key with the same JSON form, so the integer steps on the integer-keyed maps and the
+//!   numeric names behave the same.
+//! * `-` is an ordinary key on maps, even at the end of the pointer, and never resolves on a
+//!   sequence.
+//! * The indices with a leading `+` or a leading zero never resolve.
+//!
+//! Not emulated are the bytes, which `serde_json` serializes as sequences of numbers, the unit
+//! variants, the float and char keys and the maps containing a string key and an integer key of
+//! the same string form, which collapse into one key in JSON.
+
+use crate::traverse;
+use serde_value::Value;
+
+/// Finds the value the pointer points to the way `serde_json::Value::pointer()` would in the JSON
+/// form of the value. Returns `None` for the pointers `serde_json` doesn't accept.
+pub fn resolve_like_serde_json<'a>(root: &'a Value, ptr: &str) -> Option<&'a Value> {
+    if ptr.is_empty() {
+        return Some(root);
+    }
+    if !ptr.starts_with('/') {
+        return None;
+    }
+
+    ptr.split('/')
+        .skip(1)
+        .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+        .try_fold(root, |target, token| match _transparent(target) {
+            Value::Map(map) => map
+                .get(&Value::String(token.clone()))
+                .or_else(|| {
+                    map.iter()
+                        .find(|(k, _)| {
+                            !matches!(k, Value::String(_))
+                                && traverse::key_name(map, k).as_ref() == Some(&token)
+                        })
+                        .map(|(_, v)| v)
+                })
+                .map(_transparent),
+            Value::Seq(seq) => _parse_index(&token)
+                .and_then(|i| seq.get(i))
+                .map(_transparent),
+            _ => None,
+        })
+}
+
+/// The values serialized as their content in JSON.
+fn _transparent(val: &Value) -> &Value {
+    match val {
+        Value::Option(Some(inner)) | Value::Newtype(inner) => _transparent(inner),
+        _ => val,
+    }
+}
+
+fn _parse_index(s: &str) -> Option<usize> {
+    if s.starts_with('+') || (s.starts_with('0') && s.len() != 1) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pointer::Pointer;
+    use std::str::FromStr;
+
+    fn map(entries: Vec<(Value, Value)>) -> Value {
+        Value::Map(entries.into_iter().collect())
+    }
+
+    fn s(s: &str) -> Value {
+        Value::String(s.into())
+    }
+
+    #[test]
+    fn differences() {
+        let v = map(vec![
+            (s("a~2"), Value::I32(1)),
+            (s("-"), Value::I32(2)),
+            (s("01"), Value::I32(3)),
+            (
+                s("n"),
+                Value::Newtype(Box::new(map(vec![(Value::U8(1), Value::I32(4))]))),
+            ),
+            (s("seq"), Value::Seq(vec![Value::I32(5), Value::I32(6)])),
+        ]);
+        let ours = |p: &str| Pointer::from_str(p).ok().and_then(|p| p.find(&v));
+        let theirs = |p: &str| resolve_like_serde_json(&v, p);
+
+        assert_eq!(None, ours("/a~2"));
+        assert_eq!(Some(&Value::I32(1)), theirs("/a~2"));
+
+        assert_eq!(Some(&Value::I32(3)), crate::resolve_str("#/01", &v));
+        assert_eq!(None, theirs("#/01"));
+
+        assert_eq!(None, ours("/-"));
+        assert_eq!(Some(&Value::I32(2)), theirs("/-"));
+
+        assert_eq!(None, ours("/n/1"));
+        assert_eq!(Some(&Value::I32(4)), theirs("/n/1"));
+
+        // the same in both
+        assert_eq!(ours("/01"), theirs("/01"));
+        assert_eq!(ours("/seq/1"), theirs("/seq/1"));
+        assert_eq!(None, theirs("/seq/01"));
+        assert_eq!(None, theirs("/seq/+1"));
+        assert_eq!(None, theirs("/seq/-"));
+        assert_eq!(None, theirs("seq"));
+        assert_eq!(Some(&v), theirs(""));
+    }
+}
@@ -0,0 +1,158 @@
+/*
+*   Copyright (c) 2019 Lukas Krejci
+*   All rights reserved.
+
+*   Licensed under the Apache License, Version 2.0 (the "License");
+*   you may not use this file except in compliance with the License.
+*   You may obtain a copy of the License at
+
+*   http://www.apache.org/licenses/LICENSE-2.0
+
+*   Unless required by applicable law or agreed to in writing, software
+*   distributed under the License is distributed on an "AS IS" BASIS,
+*   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*   See the License for the specific language governing permissions and
+*   limitations under the License.
+*/
+
+use serde_value::Value;
+
+/// Hashes the value using 64-bit FNV-1a. Unlike the `DefaultHasher`, the algorithm is fixed, so the
+/// hashes don't change between the Rust releases.
+///
+/// The value is fed to the hasher in an encoding defined here rather than through the `Hash`
+/// impls, which give no guarantees about what they write. Each value is a tag byte identifying
+/// the variant followed by its content:
+///
+/// * the integers as their little-endian bytes, `char` as `u32`,
+/// * the floats as the little-endian bytes of their bits, with all the zeros and all the NaNs
+///   written the same, because they are equal values,
+/// * the strings and the bytes as the `u64` length followed by the bytes,
+/// * the options as `0` for `None` and `1` followed by the value for `Some`,
+/// * the sequences as the `u64` length followed by the elements and the maps as the `u64` length
+///   followed by the keys each followed by its value, in the key order.
+pub(crate) fn hash(val: &Value) -> u64 {
+    let mut hasher = Fnv1a(0xcbf2_9ce4_8422_2325);
+    _encode(val, &mut hasher);
+    hasher.0
+}
+
+fn _encode(val: &Value, hasher: &mut Fnv1a) {
+    match val {
+        Value::Bool(b) => hasher.write(&[0, *b as u8]),
+        Value::U8(i) => hasher.tagged(1, &i.to_le_bytes()),
+        Value::U16(i) => hasher.tagged(2, &i.to_le_bytes()),
+        Value::U32(i) => hasher.tagged(3, &i.to_le_bytes()),
+        Value::U64(i) => hasher.tagged(4, &i.to_le_bytes()),
+        Value::I8(i) => hasher.tagged(5, &i.to_le_bytes()),
+        Value::I16(i) => hasher.tagged(6, &i.to_le_bytes()),
+        Value::I32(i) => hasher.tagged(7, &i.to_le_bytes()),
+        Value::I64(i) => hasher.tagged(8, &i.to_le_bytes()),
+        Value::F32(f) => {
+            let bits = if *f == 0.0 {
+                0
+            } else if f.is_nan() {
+                f32::NAN.to_bits()
+            } else {
+                f.to_bits()
+            };
+            hasher.tagged(9, &bits.to_le_bytes())
+        }
+        Value::F64(f) => {
+            let bits = if *f == 0.0 {
+                0
+            } else if f.is_nan() {
+                f64::NAN.to_bits()
+            } else {
+                f.to_bits()
+            };
+            hasher.tagged(10, &bits.to_le_bytes())
+        }
+        Value::Char(c) => hasher.tagged(11, &u32::from(*c).to_le_bytes()),
+        Value::String(s) => {
+            hasher.tagged(12, &(s.len() as u64).to_le_bytes());
+            hasher.write(s.as_bytes());
+        }
+        Value::Unit => hasher.write(&[13]),
+        Value::Option(None) => hasher.write(&[14, 0]),
+        Value::Option(Some(v)) => {
+            hasher.write(&[14, 1]);
+            _encode(v, hasher);
+        }
+        Value::Newtype(v) => {
+            hasher.write(&[15]);
+            _encode(v, hasher);
+        }
+        Value::Seq(seq) => {
+            hasher.tagged(16, &(seq.len() as u64).to_le_bytes());
+            for v in seq {
+                _encode(v, hasher);
+            }
+        }
+        Value::Map(map) => {
+            hasher.tagged(17, &(map.len() as u64).to_le_bytes());
+            for (k, v) in map {
+                _encode(k, hasher);
+                _encode(v, hasher);
+            }
+        }
+        Value::Bytes(bytes) => {
+            hasher.tagged(18, &(bytes.len() as u64).to_le_bytes());
+            hasher.write(bytes);
+        }
+    }
+}
+
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= u64::from(*b);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn tagged(&mut self, tag: u8, bytes: &[u8]) {
+        self.write(&[tag]);
+        self.write(bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fnv(bytes: &[u8]) -> u64 {
+        let mut hasher = Fnv1a(0xcbf2_9ce4_8422_2325);
+        hasher.write(bytes);
+        hasher.0
+    }
+
+    #[test]
+    fn fixed_algorithm() {
+        assert_eq!(0xaf63_dc4c_8601_ec8c, fnv(b"a"));
+        assert_eq!(fnv(&[0, 1]), hash(&Value::Bool(true)));
+    }
+
+    #[test]
+    fn fixed_encoding() {
+        assert_eq!(fnv(&[3, 4, 3, 2, 1]), hash(&Value::U32(0x0102_0304)));
+        assert_eq!(
+            fnv(&[16, 1, 0, 0, 0, 0, 0, 0, 0, 12, 1, 0, 0, 0, 0, 0, 0, 0, b'a']),
+            hash(&Value::Seq(vec![Value::String("a".into())]))
+        );
+        assert_eq!(
+            fnv(&[14, 1, 13]),
+            hash(&Value::Option(Some(Box::new(Value::Unit))))
+        );
+        assert_ne!(hash(&Value::U8(1)), hash(&Value::U16(1)));
+    }
+
+    #[test]
+    fn equal_floats_hash_the_same() {
+        assert_eq!(hash(&Value::F64(0.0)), hash(&Value::F64(-0.0)));
+        assert_eq!(hash(&Value::F32(f32::NAN)), hash(&Value::F32(-f32::NAN)));
+        assert_ne!(hash(&Value::F64(1.0)), hash(&Value::F64(-1.0)));
+    }
+}
@@ -0,0 +1,98 @@
+/*
+*   Copyright (c) 2019 Lukas Krejci
+*   All rights reserved.
+
+*   Licensed under the Apache License, Version 2.0 (the "License");
+*   you may not use this file except in compliance with the License.
+*   You may obtain a copy of the License at
+
+*   http://www.apache.org/licenses/LICENSE-2.0
+
+*   Unless required by applicable law or agreed to in writing, software
+*   distributed under the License is distributed on an "AS IS" BASIS,
+*   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*   See the License for the specific language governing permissions and
+*   limitations under the License.
+*/
+
+use crate::pointer::Pointer;
+
+/// Maps the names of the struct fields to the names serde (de)serializes them under. This is what
+/// the [`pointer_for!`](crate::pointer_for) macro uses to build the pointers.
+///
+/// With the `derive` feature enabled, this can be derived using `#[derive(PointerFields)]`.
+pub trait PointerFields {
+    /// Returns the serde name of the field with the provided Rust name or `None` if there is no
+    /// such field or the field isn't serialized.
+    fn serde_field_name(field: &str) -> Option<&'static str>;
+}
+
+/// Builds a pointer to a (possibly nested) field of a struct using the names serde uses for the
+/// fields:
+///
+/// ```
+/// # #[cfg(feature = "derive")] {
+/// use serde::Serialize;
+/// use serde_pointer::{pointer_for, PointerFields};
+///
+/// #[derive(Serialize, PointerFields)]
+/// struct Config {
+///     #[serde(rename = "http")]
+///     server: Server,
+/// }
+///
+/// #[derive(Serialize, PointerFields)]
+/// struct Server {
+///     port: u16,
+/// }
+///
+/// assert_eq!("/http/port", pointer_for!(Config, server.port).to_string());
+/// # }
+/// ```
+///
+/// The field path is checked by the compiler, so a typo fails to compile. Every struct along the
+/// path has to implement [`PointerFields`]. Only the field access is supported, there is no way of
+/// stepping into sequences, maps or enums. The fields that are skipped by serde cause a panic at
+/// runtime.
+#[macro_export]
+macro_rules! pointer_for {
+    ($root:ty, $($field:ident).+) => {{
+        let mut pointer = $crate::Pointer::default();
+        $crate::__pointer_for_steps!(pointer, $root, [], $($field).+);
+        pointer
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __pointer_for_steps {
+    ($pointer:ident, $root:ty, [$($prefix:ident)*], $field:ident $(. $rest:ident)*) => {
+        $crate::__private::push_field(
+            &mut $pointer,
+            |v: &$root| &(*v) $(.$prefix)*,
+            |v: &$root| &(*v) $(.$prefix)* .$field,
+            stringify!($field),
+        );
+        $crate::__pointer_for_steps!($pointer, $root, [$($prefix)* $field], $($rest).*);
+    };
+    ($pointer:ident, $root:ty, [$($prefix:ident)*], ) => {};
+}
+
+/// The parent accessor determines the struct to look up the field name in, the field accessor
+/// only makes the compiler check that the field exists. The raw identifiers are looked up without
+/// the `r#` prefix, like the derive names them.
+#[doc(hidden)]
+pub fn push_field<R, P: PointerFields, F>(
+    pointer: &mut Pointer,
+    _parent: fn(&R) -> &P,
+    _field: fn(&R) -> &F,
+    field: &str,
+) {
+    let field = field.strip_prefix("r#").unwrap_or(field);
+    match P::serde_field_name(field) {
+        Some(name) => {
+            pointer.push_name(name);
+        }
+        None => panic!("The field '{}' is not serialized by serde.", field),
+    }
+}
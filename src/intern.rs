@@ -0,0 +1,141 @@
+/*
+*   Copyright (c) 2019 Lukas Krejci
+*   All rights reserved.
+
+*   Licensed under the Apache License, Version 2.0 (the "License");
+*   you may not use this file except in compliance with the License.
+*   You may obtain a copy of the License at
+
+*   http://www.apache.org/licenses/LICENSE-2.0
+
+*   Unless required by applicable law or agreed to in writing, software
+*   distributed under the License is distributed on an "AS IS" BASIS,
+*   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*   See the License for the specific language governing permissions and
+*   limitations under the License.
+*/
+
+use crate::parser::{self, ParseError};
+use crate::pointer::{Pointer, Step};
+use serde_value::Value;
+use std::collections::HashSet;
+use std::fmt;
+use std::fmt::Display;
+use std::sync::Arc;
+
+/// Hands out shared copies of the strings, so that each distinct string is only stored once.
+#[derive(Debug, Clone, Default)]
+pub struct StringInterner {
+    strings: HashSet<Arc<str>>,
+}
+
+/// A pointer whose names are shared with the other pointers parsed using the same interner. This
+/// is a compact storage form, convert it to a `Pointer` to work with it.
+#[derive(Eq, PartialEq, Debug, Clone, Default)]
+pub struct InternedPointer {
+    steps: Vec<InternedStep>,
+}
+
+/// The step of an interned pointer. Only the names are shared, the rest is stored as in `Step`.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum InternedStep {
+    /// A name step with the name shared through the interner.
+    Name(Arc<str>),
+
+    /// Any other step, kept as it is. The parser never puts a name step here.
+    Other(Step),
+}
+
+impl StringInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shared copy of the string, storing it first if this is the first time the
+    /// string is seen.
+    pub fn intern(&mut self, s: &str) -> Arc<str> {
+        match self.strings.get(s) {
+            Some(shared) => shared.clone(),
+            None => {
+                let shared: Arc<str> = Arc::from(s);
+                self.strings.insert(shared.clone());
+                shared
+            }
+        }
+    }
+
+    /// The number of the distinct strings stored.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+impl InternedPointer {
+    pub fn steps(&self) -> &[InternedStep] {
+        &self.steps
+    }
+
+    /// Converts to a regular pointer, copying the names.
+    pub fn to_pointer(&self) -> Pointer {
+        Pointer::from(
+            self.steps
+                .iter()
+                .map(|step| match step {
+                    InternedStep::Name(name) => Step::Name(name.to_string()),
+                    InternedStep::Other(step) => step.clone(),
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Finds the value the pointer points to. This converts the pointer first, use `to_pointer()`
+    /// to look up many values with the same pointer.
+    pub fn find<'a>(&self, root: &'a Value) -> Option<&'a Value> {
+        self.to_pointer().find(root)
+    }
+}
+
+impl Display for InternedPointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_pointer().fmt(f)
+    }
+}
+
+pub(crate) fn parse(s: &str, interner: &mut StringInterner) -> Result<InternedPointer, ParseError> {
+    let steps: Vec<Step> = parser::parse(s)?.into();
+    Ok(InternedPointer {
+        steps: steps
+            .into_iter()
+            .map(|step| match step {
+                Step::Name(name) => InternedStep::Name(interner.intern(&name)),
+                step => InternedStep::Other(step),
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn shares_names() {
+        let mut interner = StringInterner::new();
+        let a = parse("/users/1/email", &mut interner).unwrap();
+        let b = parse("/users/2/email", &mut interner).unwrap();
+        assert_eq!(2, interner.len());
+        assert_eq!("/users/2/email", b.to_string());
+
+        match (&a.steps()[2], &b.steps()[2]) {
+            (InternedStep::Name(x), InternedStep::Name(y)) => assert!(Arc::ptr_eq(x, y)),
+            _ => panic!("expected names"),
+        }
+        assert_eq!(InternedStep::Other(Step::Index(1)), a.steps()[1]);
+        assert_eq!(Pointer::from_str("/users/1/email"), Ok(a.to_pointer()));
+    }
+}
@@ -0,0 +1,361 @@
+/*
+*   Copyright (c) 2019 Lukas Krejci
+*   All rights reserved.
+
+*   Licensed under the Apache License, Version 2.0 (the "License");
+*   you may not use this file except in compliance with the License.
+*   You may obtain a copy of the License at
+
+*   http://www.apache.org/licenses/LICENSE-2.0
+
+*   Unless required by applicable law or agreed to in writing, software
+*   distributed under the License is distributed on an "AS IS" BASIS,
+*   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*   See the License for the specific language governing permissions and
+*   limitations under the License.
+*/
+
+use crate::mutate::{self, SetError};
+use crate::pointer::{Pointer, Step};
+use crate::traverse;
+use serde_value::Value;
+
+/// An operation on a value, modelled after the JSON Patch (RFC6902) operations.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    /// Adds the value at the path. The values are inserted into the sequences, shifting the
+    /// following elements.
+    Add { path: Pointer, value: Value },
+
+    /// Removes the value at the path.
+    Remove { path: Pointer },
+
+    /// Replaces the value at the path.
+    Replace { path: Pointer, value: Value },
+
+    /// Inserts the entry into the map at the path. Unlike with `Add`, the key doesn't need to be a
+    /// string, so this restores the entries with the bool and integer keys.
+    AddEntry {
+        path: Pointer,
+        key: Value,
+        value: Value,
+    },
+}
+
+/// Records the inverses of the mutations done through the pointers so that they can be undone.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Journal {
+    operations: Vec<Operation>,
+}
+
+impl Operation {
+    /// Applies the operation to the provided value.
+    pub fn apply(&self, root: &mut Value) -> Result<(), SetError> {
+        match self {
+            Operation::Add { path, value } => mutate::add(root, path, value.clone()),
+            Operation::Remove { path } => mutate::remove(root, path)
+                .map(|_| ())
+                .ok_or(SetError::NotFound),
+            Operation::Replace { path, value } => {
+                mutate::set(root, path, value.clone()).map(|_| ())
+            }
+            Operation::AddEntry { path, key, value } => {
+                match path.find_mut(root).map(traverse::unwrap_option_mut) {
+                    Some(Value::Map(map)) => {
+                        map.insert(key.clone(), value.clone());
+                        Ok(())
+                    }
+                    Some(_) => Err(SetError::TypeMismatch(path.steps().len())),
+                    None => Err(SetError::ParentNotFound),
+                }
+            }
+        }
+    }
+}
+
+impl Journal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The recorded inverse operations, in the order the mutations were done.
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    /// Undoes all the recorded mutations by applying their inverses in the reverse order. The
+    /// journal is empty afterwards.
+    ///
+    /// If an inverse fails to apply, e.g. because the value was mutated outside of the journal,
+    /// the undo stops with the error. The value is then left partially undone and the journal
+    /// keeps the failed operation and all the ones before it.
+    pub fn undo(&mut self, root: &mut Value) -> Result<(), SetError> {
+        while let Some(op) = self.operations.last() {
+            op.apply(root)?;
+            self.operations.pop();
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn set(
+    root: &mut Value,
+    pointer: &Pointer,
+    value: Value,
+    journal: &mut Journal,
+) -> Result<Option<Value>, SetError> {
+    let path = _concrete_path(root, pointer);
+    let old = mutate::set(root, pointer, value)?;
+    journal.operations.push(match &old {
+        Some(v) => Operation::Replace {
+            path,
+            value: v.clone(),
+        },
+        None => Operation::Remove { path },
+    });
+    Ok(old)
+}
+
+pub(crate) fn remove(root: &mut Value, pointer: &Pointer, journal: &mut Journal) -> Option<Value> {
+    let pointer = &_concrete_index(root, pointer);
+    let key = _removed_key(root, pointer);
+    let old = mutate::remove(root, pointer)?;
+    journal.operations.push(match key {
+        Some(Value::String(name)) => {
+            let mut path = pointer.clone();
+            path.pop();
+            path.push(Step::Name(name));
+            Operation::Add {
+                path,
+                value: old.clone(),
+            }
+        }
+        Some(key) => {
+            let mut path = pointer.clone();
+            path.pop();
+            Operation::AddEntry {
+                path,
+                key,
+                value: old.clone(),
+            }
+        }
+        None => Operation::Add {
+            path: pointer.clone(),
+            value: old.clone(),
+        },
+    });
+    Some(old)
+}
+
+/// Replaces the trailing index from the end with the index it points to, so that the removed
+/// element is inserted back at the same position.
+fn _concrete_index(root: &Value, pointer: &Pointer) -> Pointer {
+    let mut path = pointer.clone();
+    if let Some(Step::IndexFromEnd(idx)) = path.pop() {
+        if let Some(Value::Seq(seq)) = path.find(root).map(traverse::unwrap_option) {
+            if let Some(index) = seq.len().checked_sub(idx) {
+                path.push(Step::Index(index));
+                return path;
+            }
+        }
+    }
+    pointer.clone()
+}
+
+/// Returns the key of the map entry the pointer points to, if its parent is a map.
+fn _removed_key(root: &Value, pointer: &Pointer) -> Option<Value> {
+    let (last, parent_steps) = pointer.steps().split_last()?;
+    match Pointer::from(parent_steps.to_vec())
+        .find(root)
+        .map(traverse::unwrap_option)
+    {
+        Some(Value::Map(map)) => traverse::map_key(map, last),
+        _ => None,
+    }
+}
+
+pub(crate) fn append(
+    root: &mut Value,
+    pointer: &Pointer,
+    value: Value,
+    journal: &mut Journal,
+) -> Result<(), SetError> {
+    let mut path = pointer.clone();
+    path.push(Step::NewElement);
+    let path = _concrete_path(root, &path);
+    mutate::append(root, pointer, value)?;
+    journal.operations.push(Operation::Remove { path });
+    Ok(())
+}
+
+/// Replaces the trailing new element step with the index the new element is going to end up at.
+fn _concrete_path(root: &Value, pointer: &Pointer) -> Pointer {
+    let mut path = pointer.clone();
+    if let Some(Step::NewElement) = path.pop() {
+        if let Some(Value::Seq(seq)) = path.find(root).map(traverse::unwrap_option) {
+            let len = seq.len();
+            path.push(Step::Index(len));
+            return path;
+        }
+    }
+    pointer.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::map;
+    use std::str::FromStr;
+
+    #[test]
+    fn undoes_set() {
+        let original = map(vec![("a", Value::I32(1))]);
+        let mut val = original.clone();
+        let mut journal = Journal::new();
+
+        let p = Pointer::from_str("/a").unwrap();
+        p.set_journaled(&mut val, Value::I32(2), &mut journal)
+            .unwrap();
+        assert_eq!(Some(&Value::I32(2)), p.find(&val));
+
+        journal.undo(&mut val).unwrap();
+        assert_eq!(original, val);
+        assert!(journal.operations().is_empty());
+    }
+
+    #[test]
+    fn keeps_operations_that_fail_to_undo() {
+        let mut val = map(vec![("a", map(vec![]))]);
+        let mut journal = Journal::new();
+        let p = |s| Pointer::from_str(s).unwrap();
+
+        p("/a/b")
+            .set_journaled(&mut val, Value::I32(1), &mut journal)
+            .unwrap();
+        p("/c")
+            .set_journaled(&mut val, Value::I32(2), &mut journal)
+            .unwrap();
+        p("/a").set(&mut val, Value::I32(0)).unwrap();
+
+        assert_eq!(Err(SetError::NotFound), journal.undo(&mut val));
+        assert_eq!(None, p("/c").find(&val));
+        assert_eq!(1, journal.operations().len());
+    }
+
+    #[test]
+    fn undoes_mixed_mutations() {
+        let original = map(vec![
+            ("a", Value::I32(1)),
+            ("s", Value::Seq(vec![Value::I32(1), Value::I32(2)])),
+        ]);
+        let mut val = original.clone();
+        let mut journal = Journal::new();
+        let p = |s| Pointer::from_str(s).unwrap();
+
+        p("/b")
+            .set_journaled(&mut val, Value::I32(3), &mut journal)
+            .unwrap();
+        p("/s/-")
+            .set_journaled(&mut val, Value::I32(3), &mut journal)
+            .unwrap();
+        p("/s/0").remove_journaled(&mut val, &mut journal).unwrap();
+        p("/s")
+            .append_journaled(&mut val, Value::I32(4), &mut journal)
+            .unwrap();
+        p("/a").remove_journaled(&mut val, &mut journal).unwrap();
+
+        assert_eq!(
+            map(vec![
+                ("b", Value::I32(3)),
+                (
+                    "s",
+                    Value::Seq(vec![Value::I32(2), Value::I32(3), Value::I32(4)])
+                ),
+            ]),
+            val
+        );
+
+        journal.undo(&mut val).unwrap();
+        assert_eq!(original, val);
+    }
+
+    #[test]
+    fn undoes_mutations_under_options() {
+        let some = |v| Value::Option(Some(Box::new(v)));
+        let original = map(vec![("o", some(Value::Seq(vec![Value::I32(1)])))]);
+        let mut val = original.clone();
+        let mut journal = Journal::new();
+
+        Pointer::from_str("/o/-")
+            .unwrap()
+            .set_journaled(&mut val, Value::I32(2), &mut journal)
+            .unwrap();
+        Pointer::from_str("/o")
+            .unwrap()
+            .append_journaled(&mut val, Value::I32(3), &mut journal)
+            .unwrap();
+        assert_eq!(
+            map(vec![(
+                "o",
+                some(Value::Seq(vec![
+                    Value::I32(1),
+                    Value::I32(2),
+                    Value::I32(3)
+                ]))
+            )]),
+            val
+        );
+
+        journal.undo(&mut val).unwrap();
+        assert_eq!(original, val);
+    }
+
+    #[test]
+    fn undoes_remove_from_end() {
+        let original = map(vec![(
+            "s",
+            Value::Seq(vec![Value::I32(1), Value::I32(2), Value::I32(3)]),
+        )]);
+        let mut val = original.clone();
+        let mut journal = Journal::new();
+
+        let p = Pointer::from(vec![Step::Name("s".into()), Step::IndexFromEnd(2)]);
+        assert_eq!(
+            Some(Value::I32(2)),
+            p.remove_journaled(&mut val, &mut journal)
+        );
+        assert_eq!(
+            map(vec![("s", Value::Seq(vec![Value::I32(1), Value::I32(3)]))]),
+            val
+        );
+
+        journal.undo(&mut val).unwrap();
+        assert_eq!(original, val);
+    }
+
+    #[test]
+    fn undoes_remove_of_non_string_keys() {
+        let original = Value::Map(
+            vec![
+                (Value::U8(5), Value::I32(1)),
+                (Value::Bool(true), Value::I32(2)),
+                (Value::String("5x".into()), Value::I32(3)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let mut val = original.clone();
+        let mut journal = Journal::new();
+
+        for p in &["/5", "/true", "/5x"] {
+            Pointer::from_str(p)
+                .unwrap()
+                .remove_journaled(&mut val, &mut journal)
+                .unwrap();
+        }
+        assert_eq!(Value::Map(Default::default()), val);
+
+        journal.undo(&mut val).unwrap();
+        assert_eq!(original, val);
+    }
+}
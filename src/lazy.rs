@@ -0,0 +1,145 @@
+/*
+*   Copyright (c) 2019 Lukas Krejci
+*   All rights reserved.
+
+*   Licensed under the Apache License, Version 2.0 (the "License");
+*   you may not use this file except in compliance with the License.
+*   You may obtain a copy of the License at
+
+*   http://www.apache.org/licenses/LICENSE-2.0
+
+*   Unless required by applicable law or agreed to in writing, software
+*   distributed under the License is distributed on an "AS IS" BASIS,
+*   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*   See the License for the specific language governing permissions and
+*   limitations under the License.
+*/
+
+use crate::pointer::{Pointer, Step};
+use crate::traverse;
+use serde_value::Value;
+
+/// A source of values that are materialized only when they are asked for. This makes it possible
+/// to back the pointer resolution by a memory-mapped file or an on-demand loader, so that only the
+/// nodes along the pointer path need to be produced.
+///
+/// The implementations are handles to the nodes. They are cheap to produce and a handle to a
+/// child is returned by value.
+pub trait LazyValue: Sized {
+    /// Returns the child stored under the key, if this node is a map that has it.
+    fn get_key(&self, key: &str) -> Option<Self>;
+
+    /// Returns the child on the index, if this node is a sequence that has it.
+    fn get_index(&self, index: usize) -> Option<Self>;
+}
+
+impl LazyValue for &Value {
+    fn get_key(&self, key: &str) -> Option<Self> {
+        traverse::child(self, &Step::Name(key.to_owned()))
+    }
+
+    fn get_index(&self, index: usize) -> Option<Self> {
+        traverse::child(self, &Step::Index(index))
+    }
+}
+
+/// The index steps on maps are looked up using `get_key()`, so that they address the same keys as
+/// the numeric names, like in `Pointer::find()`. Neither the wildcard nor the new element step at
+/// the end of the pointer point to an existing value, the new element step elsewhere is looked up
+/// as the `"-"` key. The indices from the end are not supported, because the sources don't report
+/// their lengths.
+pub(crate) fn traverse<V: LazyValue>(root: V, pointer: &Pointer) -> Option<V> {
+    let steps = pointer.steps();
+    let mut current = root;
+    for (pos, step) in steps.iter().enumerate() {
+        current = match &*traverse::read_step(step, pos + 1 == steps.len()) {
+            Step::Name(name) => current.get_key(name)?,
+            Step::Index(index) => match current.get_index(*index) {
+                Some(child) => child,
+                None => current.get_key(&index.to_string())?,
+            },
+            Step::NewElement | Step::Wildcard | Step::IndexFromEnd(_) => return None,
+        };
+    }
+    Some(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+
+    /// Counts the nodes handed out. Apart from the root, the nodes are only "loaded" when asked.
+    struct Counting<'a> {
+        value: &'a Value,
+        loaded: &'a Cell<usize>,
+    }
+
+    impl<'a> Counting<'a> {
+        fn load(&self, value: Option<&'a Value>) -> Option<Self> {
+            let value = value?;
+            self.loaded.set(self.loaded.get() + 1);
+            Some(Counting {
+                value,
+                loaded: self.loaded,
+            })
+        }
+    }
+
+    impl<'a> LazyValue for Counting<'a> {
+        fn get_key(&self, key: &str) -> Option<Self> {
+            self.load(self.value.get_key(key))
+        }
+
+        fn get_index(&self, index: usize) -> Option<Self> {
+            self.load(self.value.get_index(index))
+        }
+    }
+
+    #[test]
+    fn loads_only_path() {
+        let mut inner = BTreeMap::new();
+        inner.insert(
+            Value::String("x".into()),
+            Value::Seq(vec![Value::U8(1), Value::U8(2)]),
+        );
+        inner.insert(Value::String("y".into()), Value::Seq(vec![Value::U8(3)]));
+        let mut root = BTreeMap::new();
+        root.insert(Value::String("a".into()), Value::Map(inner));
+        root.insert(Value::String("b".into()), Value::Bool(true));
+        let root = Value::Map(root);
+
+        let loaded = Cell::new(0);
+        let source = Counting {
+            value: &root,
+            loaded: &loaded,
+        };
+        let found = traverse(source, &Pointer::from_str("/a/x/1").unwrap()).unwrap();
+        assert_eq!(&Value::U8(2), found.value);
+        assert_eq!(3, loaded.get());
+
+        assert_eq!(
+            Some(&Value::Bool(true)),
+            traverse(&root, &Pointer::from_str("/b").unwrap())
+        );
+        assert_eq!(None, traverse(&root, &Pointer::from_str("/a/z").unwrap()));
+    }
+
+    #[test]
+    fn agrees_with_find() {
+        let keyed = |key: Value| Value::Map(vec![(key, Value::Bool(true))].into_iter().collect());
+        for root in [
+            keyed(Value::String("5".into())),
+            keyed(Value::U8(5)),
+            Value::Seq(vec![Value::Unit; 6]),
+            Value::Seq(vec![]),
+        ] {
+            for p in &["/5", "/05", "/-/x"] {
+                let p = Pointer::from_str(p).unwrap();
+                assert_eq!(p.find(&root), traverse(&root, &p), "{} in {:?}", p, root);
+            }
+        }
+    }
+}
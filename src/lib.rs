@@ -15,8 +15,31 @@
 *   limitations under the License.
 */
 
+pub use fields::PointerFields;
+pub use lazy::LazyValue;
 pub use pointer::*;
+#[cfg(feature = "derive")]
+pub use serde_pointer_derive::PointerFields;
 
+pub mod compat;
+pub mod prelude;
+pub mod schema;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::fields::push_field;
+}
+
+mod compact;
+mod digest;
+mod fields;
+mod intern;
+mod journal;
+mod lazy;
+mod mutate;
 mod parser;
 mod pointer;
+mod step_array;
+#[cfg(test)]
+mod test_util;
 mod traverse;
@@ -0,0 +1,642 @@
+/*
+*   Copyright (c) 2019 Lukas Krejci
+*   All rights reserved.
+
+*   Licensed under the Apache License, Version 2.0 (the "License");
+*   you may not use this file except in compliance with the License.
+*   You may obtain a copy of the License at
+
+*   http://www.apache.org/licenses/LICENSE-2.0
+
+*   Unless required by applicable law or agreed to in writing, software
+*   distributed under the License is distributed on an "AS IS" BASIS,
+*   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*   See the License for the specific language governing permissions and
+*   limitations under the License.
+*/
+
+use crate::pointer::{Pointer, Step};
+use crate::traverse;
+use serde_value::Value;
+use std::collections::BTreeMap;
+use std::error::Error as StdError;
+use std::fmt;
+use std::fmt::Display;
+
+/// The error returned when merging the data at one pointer into the data at another pointer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeError {
+    /// The source pointer doesn't point to any existing value.
+    SourceNotFound,
+
+    /// The destination pointer doesn't point to any existing value.
+    DestinationNotFound,
+}
+
+/// The error returned when setting the value at a pointer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SetError {
+    /// The parent of the value to set doesn't exist.
+    ParentNotFound,
+
+    /// The step at the given position cannot be applied to the existing value. E.g. a name step
+    /// used on a sequence.
+    TypeMismatch(usize),
+
+    /// The index step at the given position points past the end of the sequence. It is possible to
+    /// set the existing elements or to append right after the last one.
+    IndexOutOfBounds(usize),
+
+    /// The value to remove doesn't exist.
+    NotFound,
+}
+
+/// The error returned when rebuilding a value from the pointers and the values at them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnflattenError {
+    /// The value at the pointer conflicts with a value at another pointer. Either the same pointer
+    /// is used twice, or one pointer is a prefix of another, or the steps of the pointers don't
+    /// match, e.g. a name and an index step is used on the same value.
+    Conflict(Pointer),
+}
+
+pub(crate) fn unflatten<I: IntoIterator<Item = (Pointer, Value)>>(
+    pairs: I,
+) -> Result<Value, UnflattenError> {
+    let mut root: Option<Value> = None;
+    for (pointer, value) in pairs {
+        let root = match root.as_mut() {
+            Some(root) => root,
+            None if pointer.is_root() => {
+                root = Some(value);
+                continue;
+            }
+            None => root.get_or_insert(traverse::container_for(pointer.steps().first())),
+        };
+
+        if pointer.find(root).is_some() {
+            return Err(UnflattenError::Conflict(pointer));
+        }
+
+        match pointer.ensure_path(root) {
+            Ok(leaf) => *leaf = value,
+            Err(_) => return Err(UnflattenError::Conflict(pointer)),
+        }
+    }
+
+    Ok(root.unwrap_or_else(|| Value::Map(BTreeMap::new())))
+}
+
+pub(crate) fn set(
+    root: &mut Value,
+    pointer: &Pointer,
+    value: Value,
+) -> Result<Option<Value>, SetError> {
+    let steps = pointer.steps();
+    let (last, parent_steps) = match steps.split_last() {
+        Some(split) => split,
+        None => return Ok(Some(std::mem::replace(root, value))),
+    };
+
+    let parent = Pointer::from(parent_steps.to_vec())
+        .find_mut(root)
+        .ok_or(SetError::ParentNotFound)?;
+
+    _set_child(parent, last, parent_steps.len(), value)
+}
+
+pub(crate) fn remove(root: &mut Value, pointer: &Pointer) -> Option<Value> {
+    let (last, parent_steps) = pointer.steps().split_last()?;
+    let parent = Pointer::from(parent_steps.to_vec()).find_mut(root)?;
+
+    match (last, traverse::unwrap_option_mut(parent)) {
+        (Step::Index(index), Value::Seq(seq)) if *index < seq.len() => Some(seq.remove(*index)),
+        (Step::IndexFromEnd(index), Value::Seq(seq)) if *index >= 1 && *index <= seq.len() => {
+            Some(seq.remove(seq.len() - index))
+        }
+        (_, Value::Map(map)) => {
+            let key = traverse::map_key(map, last)?;
+            map.remove(&key)
+        }
+        _ => None,
+    }
+}
+
+pub(crate) fn append(root: &mut Value, pointer: &Pointer, value: Value) -> Result<(), SetError> {
+    match pointer.find_mut(root).map(traverse::unwrap_option_mut) {
+        Some(Value::Seq(seq)) => {
+            seq.push(value);
+            Ok(())
+        }
+        Some(_) => Err(SetError::TypeMismatch(pointer.steps().len())),
+        None => Err(SetError::ParentNotFound),
+    }
+}
+
+/// Adds the value the way the JSON Patch `add` operation does. Unlike `set()`, this inserts into
+/// the sequences instead of replacing the elements.
+pub(crate) fn add(root: &mut Value, pointer: &Pointer, value: Value) -> Result<(), SetError> {
+    if let Some((Step::Index(index), parent_steps)) = pointer.steps().split_last() {
+        let parent = Pointer::from(parent_steps.to_vec()).find_mut(root);
+        if let Some(Value::Seq(seq)) = parent.map(traverse::unwrap_option_mut) {
+            if *index > seq.len() {
+                return Err(SetError::IndexOutOfBounds(parent_steps.len()));
+            }
+            seq.insert(*index, value);
+            return Ok(());
+        }
+    }
+
+    set(root, pointer, value).map(|_| ())
+}
+
+pub(crate) fn set_force(
+    root: &mut Value,
+    pointer: &Pointer,
+    value: Value,
+) -> Result<Option<Value>, SetError> {
+    let steps = pointer.steps();
+    let (last, parent_steps) = match steps.split_last() {
+        Some(split) => split,
+        None => return Ok(Some(std::mem::replace(root, value))),
+    };
+
+    let mut current = root;
+    for (pos, step) in parent_steps.iter().enumerate() {
+        let parent = traverse::unwrap_option_mut(current);
+        _coerce(parent, step);
+        if traverse::child(parent, step).is_none() {
+            _set_child(parent, step, pos, Value::Unit)?;
+        }
+        current = match step {
+            Step::NewElement => match parent {
+                Value::Seq(seq) => seq.last_mut().unwrap(),
+                _ => unreachable!(),
+            },
+            _ => traverse::child_mut(parent, step).unwrap(),
+        };
+    }
+
+    let parent = traverse::unwrap_option_mut(current);
+    _coerce(parent, last);
+    _set_child(parent, last, parent_steps.len(), value)
+}
+
+pub(crate) fn find_mut_or_insert<'a>(
+    root: &'a mut Value,
+    pointer: &Pointer,
+    default: Value,
+) -> Option<&'a mut Value> {
+    // checking first keeps the borrow checker happy about returning the found value
+    if pointer.find(root).is_some() {
+        return pointer.find_mut(root);
+    }
+
+    let (last, parent_steps) = pointer.steps().split_last()?;
+    let parent = Pointer::from(parent_steps.to_vec()).find_mut(root)?;
+    match (last, traverse::unwrap_option_mut(parent)) {
+        (Step::Name(_), Value::Map(map)) | (Step::Index(_), Value::Map(map)) => {
+            Some(map.entry(traverse::new_key(last)).or_insert(default))
+        }
+        _ => None,
+    }
+}
+
+/// Overwrites the existing string in place, so that its buffer is reused if it's large enough.
+pub(crate) fn set_str(root: &mut Value, pointer: &Pointer, value: &str) -> Result<(), SetError> {
+    match pointer.find_mut(root).map(traverse::unwrap_option_mut) {
+        Some(Value::String(existing)) => {
+            existing.clear();
+            existing.push_str(value);
+            Ok(())
+        }
+        _ => set(root, pointer, Value::String(value.to_owned())).map(|_| ()),
+    }
+}
+
+/// Replaces the value with an empty container the step can be applied to, unless it already is one.
+/// The present optional values need to be looked through before calling this.
+fn _coerce(val: &mut Value, step: &Step) {
+    match (step, &val) {
+        (Step::Name(_), Value::Map(_)) => {}
+        (Step::Name(_), _) => *val = Value::Map(BTreeMap::new()),
+        (Step::Index(_), Value::Seq(_)) | (Step::NewElement, Value::Seq(_)) => {}
+        (Step::Index(_), _) | (Step::NewElement, _) => *val = Value::Seq(Vec::new()),
+        (Step::Wildcard, _) | (Step::IndexFromEnd(_), _) => {}
+    }
+}
+
+fn _set_child(
+    parent: &mut Value,
+    step: &Step,
+    pos: usize,
+    value: Value,
+) -> Result<Option<Value>, SetError> {
+    if step != &Step::NewElement && traverse::child(parent, step).is_some() {
+        let existing = traverse::child_mut(parent, step).unwrap();
+        return Ok(Some(std::mem::replace(existing, value)));
+    }
+
+    match (step, traverse::unwrap_option_mut(parent)) {
+        (Step::Name(_), Value::Map(map)) | (Step::Index(_), Value::Map(map)) => {
+            map.insert(traverse::new_key(step), value);
+            Ok(None)
+        }
+        (Step::Index(index), Value::Seq(seq)) if *index == seq.len() => {
+            seq.push(value);
+            Ok(None)
+        }
+        (Step::Index(_), Value::Seq(_)) | (Step::IndexFromEnd(_), Value::Seq(_)) => {
+            Err(SetError::IndexOutOfBounds(pos))
+        }
+        (Step::NewElement, Value::Seq(seq)) => {
+            seq.push(value);
+            Ok(None)
+        }
+        _ => Err(SetError::TypeMismatch(pos)),
+    }
+}
+
+pub(crate) fn merge_into(src: &Pointer, dst: &Pointer, root: &mut Value) -> Result<(), MergeError> {
+    let src_val = src.find(root).ok_or(MergeError::SourceNotFound)?.clone();
+    let dst_val = dst.find_mut(root).ok_or(MergeError::DestinationNotFound)?;
+    _deep_merge(dst_val, src_val);
+    Ok(())
+}
+
+fn _deep_merge(dst: &mut Value, src: Value) {
+    match (dst, src) {
+        (Value::Map(dst_map), Value::Map(src_map)) => {
+            for (k, v) in src_map {
+                match dst_map.get_mut(&k) {
+                    Some(existing) => _deep_merge(existing, v),
+                    None => {
+                        dst_map.insert(k, v);
+                    }
+                }
+            }
+        }
+        (dst, src) => *dst = src,
+    }
+}
+
+impl Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeError::SourceNotFound => f.write_str("Cannot merge: source value not found"),
+            MergeError::DestinationNotFound => {
+                f.write_str("Cannot merge: destination value not found")
+            }
+        }
+    }
+}
+
+impl StdError for MergeError {}
+
+impl Display for UnflattenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnflattenError::Conflict(p) => f.write_fmt(format_args!(
+                "Cannot unflatten: value at {} conflicts with another value",
+                p
+            )),
+        }
+    }
+}
+
+impl StdError for UnflattenError {}
+
+impl Display for SetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetError::ParentNotFound => f.write_str("Cannot set value: parent not found"),
+            SetError::TypeMismatch(pos) => f.write_fmt(format_args!(
+                "Cannot set value: step at position {} doesn't match the type of the value",
+                pos
+            )),
+            SetError::IndexOutOfBounds(pos) => f.write_fmt(format_args!(
+                "Cannot set value: index at position {} is out of bounds",
+                pos
+            )),
+            SetError::NotFound => f.write_str("Cannot remove value: value not found"),
+        }
+    }
+}
+
+impl StdError for SetError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pointer::ParseOptions;
+    use crate::test_util::map;
+    use std::str::FromStr;
+
+    #[test]
+    fn merges_maps() {
+        let mut val = map(vec![
+            (
+                "src",
+                map(vec![
+                    ("a", Value::I32(1)),
+                    ("nested", map(vec![("x", Value::Bool(true))])),
+                    ("extra", Value::I32(3)),
+                ]),
+            ),
+            (
+                "dst",
+                map(vec![
+                    ("a", Value::I32(0)),
+                    ("nested", map(vec![("y", Value::Bool(false))])),
+                ]),
+            ),
+        ]);
+        let src = Pointer::from_str("/src").unwrap();
+        let dst = Pointer::from_str("/dst").unwrap();
+
+        Pointer::merge_into(&src, &dst, &mut val).unwrap();
+
+        assert_eq!(
+            Some(&map(vec![
+                ("a", Value::I32(1)),
+                (
+                    "nested",
+                    map(vec![("x", Value::Bool(true)), ("y", Value::Bool(false))])
+                ),
+                ("extra", Value::I32(3)),
+            ])),
+            dst.find(&val)
+        );
+    }
+
+    #[test]
+    fn merge_fails_on_missing_pointers() {
+        let mut val = map(vec![("a", Value::I32(1))]);
+        let a = Pointer::from_str("/a").unwrap();
+        let missing = Pointer::from_str("/b").unwrap();
+
+        assert_eq!(
+            Err(MergeError::SourceNotFound),
+            Pointer::merge_into(&missing, &a, &mut val)
+        );
+        assert_eq!(
+            Err(MergeError::DestinationNotFound),
+            Pointer::merge_into(&a, &missing, &mut val)
+        );
+    }
+
+    #[test]
+    fn sets_values() {
+        let mut val = map(vec![
+            ("a", map(vec![("b", Value::I32(1))])),
+            ("s", Value::Seq(vec![Value::I32(1)])),
+        ]);
+        let set = |val: &mut Value, p, v| Pointer::from_str(p).unwrap().set(val, v);
+
+        assert_eq!(
+            Ok(Some(Value::I32(1))),
+            set(&mut val, "/a/b", Value::I32(2))
+        );
+        assert_eq!(Ok(None), set(&mut val, "/a/c", Value::I32(3)));
+        assert_eq!(Ok(None), set(&mut val, "/s/-", Value::I32(4)));
+        assert_eq!(Ok(None), set(&mut val, "/s/2", Value::I32(5)));
+        assert_eq!(
+            map(vec![
+                ("a", map(vec![("b", Value::I32(2)), ("c", Value::I32(3))])),
+                (
+                    "s",
+                    Value::Seq(vec![Value::I32(1), Value::I32(4), Value::I32(5)])
+                ),
+            ]),
+            val
+        );
+
+        assert_eq!(
+            Err(SetError::IndexOutOfBounds(1)),
+            set(&mut val, "/s/5", Value::Unit)
+        );
+        assert_eq!(
+            Err(SetError::TypeMismatch(2)),
+            set(&mut val, "/a/b/c", Value::Unit)
+        );
+        assert_eq!(
+            Err(SetError::ParentNotFound),
+            set(&mut val, "/x/y", Value::Unit)
+        );
+    }
+
+    #[test]
+    fn set_force_replaces_scalar_with_map() {
+        let mut val = map(vec![("a", Value::String("scalar".into()))]);
+        let p = Pointer::from_str("/a/b").unwrap();
+
+        assert_eq!(
+            Err(SetError::TypeMismatch(1)),
+            p.set(&mut val, Value::I32(1))
+        );
+        assert_eq!(Ok(None), p.set_force(&mut val, Value::I32(1)));
+        assert_eq!(map(vec![("a", map(vec![("b", Value::I32(1))]))]), val);
+    }
+
+    #[test]
+    fn set_force_looks_through_options() {
+        let some = |v| Value::Option(Some(Box::new(v)));
+        let mut val = map(vec![("opt", some(map(vec![("a", Value::I32(1))])))]);
+
+        Pointer::from_str("/opt/b")
+            .unwrap()
+            .set_force(&mut val, Value::I32(2))
+            .unwrap();
+        assert_eq!(
+            map(vec![(
+                "opt",
+                some(map(vec![("a", Value::I32(1)), ("b", Value::I32(2))]))
+            )]),
+            val
+        );
+
+        let s = Pointer::from_str("/s").unwrap();
+        let mut val = map(vec![("s", some(Value::Seq(vec![])))]);
+        assert_eq!(Ok(()), s.append(&mut val, Value::I32(1)));
+        assert_eq!(map(vec![("s", some(Value::Seq(vec![Value::I32(1)])))]), val);
+    }
+
+    #[test]
+    fn set_force_creates_missing_path() {
+        let mut val = Value::Unit;
+
+        Pointer::from_str("/a/0/b")
+            .unwrap()
+            .set_force(&mut val, Value::Bool(true))
+            .unwrap();
+
+        assert_eq!(
+            map(vec![(
+                "a",
+                Value::Seq(vec![map(vec![("b", Value::Bool(true))])])
+            )]),
+            val
+        );
+    }
+
+    #[test]
+    fn removes_values() {
+        let mut val = map(vec![
+            ("a", Value::I32(1)),
+            ("s", Value::Seq(vec![Value::I32(1), Value::I32(2)])),
+        ]);
+        let remove = |val: &mut Value, p| Pointer::from_str(p).unwrap().remove_value(val);
+
+        assert_eq!(Some(Value::I32(1)), remove(&mut val, "/a"));
+        assert_eq!(Some(Value::I32(1)), remove(&mut val, "/s/0"));
+        assert_eq!(None, remove(&mut val, "/s/1"));
+        assert_eq!(None, remove(&mut val, "/x"));
+        assert_eq!(None, remove(&mut val, ""));
+        assert_eq!(map(vec![("s", Value::Seq(vec![Value::I32(2)]))]), val);
+    }
+
+    #[test]
+    fn removes_values_from_end() {
+        let mut val = map(vec![(
+            "s",
+            Value::Seq(vec![Value::I32(1), Value::I32(2), Value::I32(3)]),
+        )]);
+        let options = ParseOptions::new().negative_indices(true);
+        let remove =
+            |val: &mut Value, p| Pointer::parse_with(p, &options).unwrap().remove_value(val);
+
+        assert_eq!(Some(Value::I32(3)), remove(&mut val, "/s/-1"));
+        assert_eq!(Some(Value::I32(1)), remove(&mut val, "/s/-2"));
+        assert_eq!(None, remove(&mut val, "/s/-2"));
+        assert_eq!(map(vec![("s", Value::Seq(vec![Value::I32(2)]))]), val);
+    }
+
+    #[test]
+    fn appends_values() {
+        let mut val = map(vec![("s", Value::Seq(vec![])), ("a", Value::I32(1))]);
+
+        let s = Pointer::from_str("/s").unwrap();
+        assert_eq!(Ok(()), s.append(&mut val, Value::I32(1)));
+        assert_eq!(Some(&Value::Seq(vec![Value::I32(1)])), s.find(&val));
+
+        let a = Pointer::from_str("/a").unwrap();
+        assert_eq!(
+            Err(SetError::TypeMismatch(1)),
+            a.append(&mut val, Value::I32(1))
+        );
+    }
+
+    #[test]
+    fn unflatten_round_trips_flatten() {
+        let val = map(vec![
+            (
+                "a",
+                Value::Seq(vec![
+                    Value::I32(1),
+                    map(vec![("b", Value::Bool(true)), ("c", Value::Unit)]),
+                ]),
+            ),
+            ("d", map(vec![("e", Value::String("x".into()))])),
+        ]);
+
+        let pairs = Pointer::flatten(&val)
+            .into_iter()
+            .map(|(p, v)| (p, v.clone()));
+
+        assert_eq!(Ok(val.clone()), Pointer::unflatten(pairs));
+    }
+
+    #[test]
+    fn unflatten_fails_on_conflicts() {
+        let p = |s| Pointer::from_str(s).unwrap();
+
+        assert_eq!(
+            Err(UnflattenError::Conflict(p("/a/b"))),
+            Pointer::unflatten(vec![(p("/a"), Value::I32(1)), (p("/a/b"), Value::I32(2))])
+        );
+        assert_eq!(
+            Err(UnflattenError::Conflict(p("/a"))),
+            Pointer::unflatten(vec![(p("/a/b"), Value::I32(2)), (p("/a"), Value::I32(1))])
+        );
+        assert_eq!(
+            Err(UnflattenError::Conflict(p("/a"))),
+            Pointer::unflatten(vec![(p("/a"), Value::I32(1)), (p("/a"), Value::I32(1))])
+        );
+    }
+
+    #[test]
+    fn set_str_reuses_buffer() {
+        let mut v = map(vec![("a", Value::String(String::with_capacity(32)))]);
+        let buf = match &v {
+            Value::Map(m) => match m.values().next() {
+                Some(Value::String(s)) => s.as_ptr(),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+
+        let p = Pointer::from_str("/a").unwrap();
+        set_str(&mut v, &p, "new").unwrap();
+        match p.find(&v) {
+            Some(Value::String(s)) => {
+                assert_eq!("new", s);
+                assert_eq!(buf, s.as_ptr());
+            }
+            _ => panic!("expected a string"),
+        }
+
+        let mut general = map(vec![("a", Value::String("new".into()))]);
+        set_str(&mut v, &Pointer::from_str("/b").unwrap(), "added").unwrap();
+        set(
+            &mut general,
+            &Pointer::from_str("/b").unwrap(),
+            Value::String("added".into()),
+        )
+        .unwrap();
+        assert_eq!(general, v);
+        assert_eq!(
+            Err(SetError::ParentNotFound),
+            set_str(&mut v, &Pointer::from_str("/x/y").unwrap(), "z")
+        );
+    }
+
+    #[test]
+    fn find_mut_or_insert_leaf() {
+        let mut v = map(vec![("a", map(vec![("b", Value::I32(1))]))]);
+
+        let found = find_mut_or_insert(&mut v, &Pointer::from_str("/a/c").unwrap(), Value::Unit);
+        *found.unwrap() = Value::I32(2);
+        assert_eq!(
+            map(vec![(
+                "a",
+                map(vec![("b", Value::I32(1)), ("c", Value::I32(2))])
+            )]),
+            v
+        );
+
+        let existing = find_mut_or_insert(&mut v, &Pointer::from_str("/a/b").unwrap(), Value::Unit);
+        assert_eq!(Some(&mut Value::I32(1)), existing);
+
+        assert_eq!(
+            None,
+            find_mut_or_insert(&mut v, &Pointer::from_str("/x/y").unwrap(), Value::Unit)
+        );
+        assert_eq!(
+            None,
+            find_mut_or_insert(&mut v, &Pointer::from_str("/a/b/c").unwrap(), Value::Unit)
+        );
+        assert_eq!(None, Pointer::from_str("/x").unwrap().find(&v));
+    }
+
+    #[test]
+    fn index_steps_create_string_keys() {
+        let mut v = map(vec![]);
+        let p = Pointer::from_str("/0").unwrap();
+        assert_eq!(Ok(None), p.set(&mut v, Value::I32(1)));
+        assert_eq!(map(vec![("0", Value::I32(1))]), v);
+
+        let found = find_mut_or_insert(&mut v, &Pointer::from_str("/1").unwrap(), Value::Unit);
+        assert_eq!(Some(&mut Value::Unit), found);
+        assert_eq!(map(vec![("0", Value::I32(1)), ("1", Value::Unit)]), v);
+    }
+}
@@ -21,7 +21,7 @@ use nom::{
     branch::alt,
     character::complete::{anychar, char, digit1, none_of},
     combinator::{all_consuming, opt},
-    error::{convert_error, ParseError as NomParseError, VerboseError},
+    error::{convert_error, ParseError as NomParseError, VerboseError, VerboseErrorKind},
     multi::many0,
     sequence::preceded,
     IResult,
@@ -32,28 +32,320 @@ use std::fmt::Display;
 use std::str;
 
 pub(crate) fn parse(s: &str) -> Result<Pointer, ParseError> {
-    let result = _parse(s);
+    let result = _parse::<(&str, nom::error::ErrorKind)>(s);
 
     match result {
         Ok(r) => Ok(r.1),
-        Err(e) => Err(ParseError {
-            error: match e {
-                nom::Err::Incomplete(_) => "Incomplete JSON Pointer".to_owned(),
-                nom::Err::Error(e) => convert_error(s, VerboseError::from_error_kind(e.0, e.1)),
-                nom::Err::Failure(e) => convert_error(s, VerboseError::from_error_kind(e.0, e.1)),
+        Err(e) => Err(match e {
+            nom::Err::Incomplete(_) => ParseError::new("Incomplete JSON Pointer".to_owned()),
+            nom::Err::Error(e) | nom::Err::Failure(e) => ParseError {
+                error: convert_error(s, VerboseError::from_error_kind(e.0, e.1)),
+                offset: Some(s.len() - e.0.len()),
             },
         }),
     }
 }
 
-fn _parse(s: &str) -> IResult<&str, Pointer> {
+/// The opt-in deviations from the strict RFC6901 parsing. All of them are disabled by default.
+///
+/// ```
+/// use serde_pointer::{ParseOptions, Pointer};
+/// use serde_value::Value;
+///
+/// let options = ParseOptions::new().negative_indices(true);
+/// let pointer = Pointer::parse_with("/-1", &options).unwrap();
+/// let value = Value::Seq(vec![Value::U8(1), Value::U8(2)]);
+/// assert_eq!(Some(&Value::U8(2)), pointer.find(&value));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    negative_indices: bool,
+    literal_last_segment: bool,
+    max_segment_len: Option<usize>,
+    trim_trailing_empty: bool,
+    names_only: bool,
+}
+
+impl ParseOptions {
+    pub fn new() -> ParseOptions {
+        ParseOptions::default()
+    }
+
+    /// Makes the segments like `-1` parse as `Step::IndexFromEnd` instead of names. This makes the
+    /// map keys in such a form unreachable.
+    pub fn negative_indices(mut self, enabled: bool) -> ParseOptions {
+        self.negative_indices = enabled;
+        self
+    }
+
+    /// Takes the last segment literally, without unescaping it. This accepts the names with a
+    /// stray `~`, like `/backups/file~`, that are otherwise rejected.
+    ///
+    /// This is ambiguous: `/a/x~1y` refers to the name `x~1y` under this option but to `x/y`
+    /// without it. Only the indices and the new element are still recognized in the last segment.
+    pub fn literal_last_segment(mut self, enabled: bool) -> ParseOptions {
+        self.literal_last_segment = enabled;
+        self
+    }
+
+    /// Rejects the segments longer than the provided number of bytes once unescaped. The lengths
+    /// are checked before any of the segments is parsed, so no memory is allocated for the
+    /// oversized input.
+    pub fn max_segment_len(mut self, max: Option<usize>) -> ParseOptions {
+        self.max_segment_len = max;
+        self
+    }
+
+    /// Drops the empty name at the end of the pointer, so that `/a/b/` is read as `/a/b`. Per
+    /// RFC6901, the trailing slash refers to the empty key under `/a/b`, which becomes unreachable
+    /// as the last step under this option. Only a single empty name is dropped.
+    pub fn trim_trailing_empty(mut self, enabled: bool) -> ParseOptions {
+        self.trim_trailing_empty = enabled;
+        self
+    }
+
+    /// Parses every segment as a name, including `-` and the digits, as produced by
+    /// `Pointer::build_escaped()`. This takes precedence over `negative_indices()`.
+    pub fn names_only(mut self, enabled: bool) -> ParseOptions {
+        self.names_only = enabled;
+        self
+    }
+}
+
+pub(crate) fn parse_with(s: &str, options: &ParseOptions) -> Result<Pointer, ParseError> {
+    if let Some(max) = options.max_segment_len {
+        _check_segment_lens(s, max, options.literal_last_segment)?;
+    }
+
+    let pointer = match s.rfind('/') {
+        Some(last) if options.literal_last_segment => {
+            let segment = &s[last + 1..];
+            let step = match parse_segment(segment) {
+                Ok(step @ Step::Index(_)) | Ok(step @ Step::NewElement) => step,
+                _ => Step::Name(segment.to_owned()),
+            };
+            let mut pointer = parse(&s[..last])?;
+            pointer.push(step);
+            pointer
+        }
+        _ => parse(s)?,
+    };
+    let mut steps: Vec<Step> = pointer.into();
+    if options.trim_trailing_empty && steps.last() == Some(&Step::Name(String::new())) {
+        steps.pop();
+    }
+    if options.names_only {
+        return Ok(Pointer::from(
+            steps.into_iter().map(_as_name).collect::<Vec<_>>(),
+        ));
+    }
+    if !options.negative_indices {
+        return Ok(Pointer::from(steps));
+    }
+
+    Ok(Pointer::from(
+        steps
+            .into_iter()
+            .map(|step| match step {
+                Step::Name(name) => match _index_from_end(&name) {
+                    Some(idx) => Step::IndexFromEnd(idx),
+                    None => Step::Name(name),
+                },
+                step => step,
+            })
+            .collect::<Vec<_>>(),
+    ))
+}
+
+/// The indices are parsed only from the digits without a leading zero, so their string form is the
+/// segment they were parsed from.
+fn _as_name(step: Step) -> Step {
+    match step {
+        Step::Index(idx) => Step::Name(idx.to_string()),
+        Step::NewElement => Step::Name("-".to_owned()),
+        step => step,
+    }
+}
+
+/// Each escape sequence unescapes to a single byte. The pointers not starting with `/` are left to
+/// the parser to report.
+fn _check_segment_lens(s: &str, max: usize, literal_last: bool) -> Result<(), ParseError> {
+    let pointer = s.strip_prefix('#').unwrap_or(s);
+    let mut offset = s.len() - pointer.len() + 1;
+    let pointer = match pointer.strip_prefix('/') {
+        Some(pointer) => pointer,
+        None => return Ok(()),
+    };
+    let segments = pointer.split('/').collect::<Vec<_>>();
+    for (i, segment) in segments.iter().enumerate() {
+        let len = if literal_last && i + 1 == segments.len() {
+            segment.len()
+        } else {
+            segment.len() - segment.matches('~').count()
+        };
+        if len > max {
+            return Err(ParseError {
+                error: format!("JSON Pointer segment longer than {} bytes", max),
+                offset: Some(offset),
+            });
+        }
+        offset += segment.len() + 1;
+    }
+    Ok(())
+}
+
+/// The offsets of the errors are into the whole list rather than into the failed token.
+pub(crate) fn parse_list(s: &str) -> Result<Vec<Pointer>, ParseError> {
+    s.split_whitespace()
+        .enumerate()
+        .map(|(index, token)| {
+            let start = token.as_ptr() as usize - s.as_ptr() as usize;
+            parse(token).map_err(|e| ParseError {
+                error: format!("Invalid JSON Pointer #{} `{}`: {}", index, token, e.error),
+                offset: Some(start + e.offset.unwrap_or(0)),
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn parse_with_rest(s: &str, fixed_segments: usize) -> Result<Pointer, ParseError> {
+    match s.match_indices('/').nth(fixed_segments) {
+        Some((rest, _)) => {
+            let mut pointer = parse(&s[..rest])?;
+            pointer.push_name(&s[rest + 1..]);
+            Ok(pointer)
+        }
+        None => parse(s),
+    }
+}
+
+/// Only the positive numbers without the leading zeros count, like the indices.
+fn _index_from_end(name: &str) -> Option<usize> {
+    let digits = name.strip_prefix('-')?;
+    if digits.is_empty() || digits.starts_with('0') || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+pub(crate) fn parse_segment(s: &str) -> Result<Step, ParseError> {
+    match all_consuming(_parse_segment::<(&str, nom::error::ErrorKind)>)(s) {
+        Ok(r) => Ok(r.1),
+        Err(nom::Err::Incomplete(_)) => Err(ParseError::new("Incomplete JSON Pointer".to_owned())),
+        Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => Err(ParseError {
+            error: convert_error(s, VerboseError::from_error_kind(e.0, e.1)),
+            offset: Some(s.len() - e.0.len()),
+        }),
+    }
+}
+
+pub(crate) fn parse_verbose(s: &str) -> Result<Pointer, VerboseParseError> {
+    match _parse::<VerboseError<&str>>(s) {
+        Ok(r) => Ok(r.1),
+        Err(nom::Err::Incomplete(_)) => Err(VerboseParseError { frames: Vec::new() }),
+        Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => Err(VerboseParseError {
+            frames: e
+                .errors
+                .into_iter()
+                .map(|(rest, kind)| (s.len() - rest.len(), kind))
+                .collect(),
+        }),
+    }
+}
+
+pub(crate) fn parse_uri_fragment(s: &str) -> Result<Pointer, ParseError> {
+    match s.strip_prefix('#') {
+        Some(fragment) => {
+            let decoded = percent_decode(fragment).map_err(|e| ParseError {
+                offset: e.offset.map(|o| o + 1),
+                ..e
+            })?;
+            // the offsets into the decoded pointer don't match the fragment
+            parse(&decoded).map_err(|e| ParseError::new(e.error))
+        }
+        None => Err(ParseError {
+            error: "URI fragment must start with '#'".to_owned(),
+            offset: Some(0),
+        }),
+    }
+}
+
+/// The query values use the form encoding, where a `+` stands for a space.
+pub(crate) fn parse_query_value(s: &str) -> Result<Pointer, ParseError> {
+    let decoded = percent_decode(&s.replace('+', " "))?;
+    // the offsets into the decoded pointer don't match the query value
+    parse(&decoded).map_err(|e| ParseError::new(e.error))
+}
+
+pub(crate) fn percent_decode(s: &str) -> Result<String, ParseError> {
+    let invalid = |pos: usize| ParseError {
+        error: format!("Invalid percent-encoding at position {}", pos),
+        offset: Some(pos),
+    };
+
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = s
+                .get(i + 1..i + 3)
+                .filter(|h| h.bytes().all(|b| b.is_ascii_hexdigit()))
+                .ok_or_else(|| invalid(i))?;
+            let byte = u8::from_str_radix(hex, 16).map_err(|_| invalid(i))?;
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(decoded)
+        .map_err(|_| ParseError::new("Percent-encoded data is not valid UTF-8".to_owned()))
+}
+
+/// Percent-encodes all the characters that are not allowed in the URI fragment.
+pub(crate) fn percent_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'.'
+            | b'_'
+            | b'~'
+            | b'!'
+            | b'$'
+            | b'&'
+            | b'\''
+            | b'('
+            | b')'
+            | b'*'
+            | b'+'
+            | b','
+            | b';'
+            | b'='
+            | b':'
+            | b'@'
+            | b'/'
+            | b'?' => encoded.push(b as char),
+            _ => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+fn _parse<'a, E: NomParseError<&'a str>>(s: &'a str) -> IResult<&'a str, Pointer, E> {
     let (s, _) = opt(char('#'))(s)?;
     let (s, segments) = all_consuming(many0(preceded(char('/'), _parse_segment)))(s)?;
 
     Ok((s, segments.into()))
 }
 
-fn _parse_segment(s: &str) -> IResult<&str, Step> {
+fn _parse_segment<'a, E: NomParseError<&'a str>>(s: &'a str) -> IResult<&'a str, Step, E> {
     if s.is_empty() {
         return Ok((s, Step::Name(String::new())));
     }
@@ -61,8 +353,8 @@ fn _parse_segment(s: &str) -> IResult<&str, Step> {
     alt((_parse_index, _parse_new_element, _parse_name))(s)
 }
 
-fn _parse_index(s: &str) -> IResult<&str, Step> {
-    let leading_zero = char::<_, (&str, nom::error::ErrorKind)>('0')(s);
+fn _parse_index<'a, E: NomParseError<&'a str>>(s: &'a str) -> IResult<&'a str, Step, E> {
+    let leading_zero = char::<_, E>('0')(s);
     match leading_zero {
         Ok((rest, _)) => {
             if segment_ends(rest) {
@@ -73,21 +365,25 @@ fn _parse_index(s: &str) -> IResult<&str, Step> {
         }
         Err(_) => {
             // not a leading 0
-            let (s, ds) = digit1(s)?;
+            let (rest, ds) = digit1(s)?;
+            // the digits followed by other characters, e.g. "12ab", are a name
+            if !segment_ends(rest) {
+                return _parse_name(s);
+            }
             let idx = ds.parse::<usize>().map_err(|_| {
-                nom::Err::Error(nom::error::make_error(s, nom::error::ErrorKind::Digit))
+                nom::Err::Error(E::from_error_kind(s, nom::error::ErrorKind::Digit))
             })?;
-            Ok((s, Step::Index(idx)))
+            Ok((rest, Step::Index(idx)))
         }
     }
 }
 
-fn _parse_name(s: &str) -> IResult<&str, Step> {
+fn _parse_name<'a, E: NomParseError<&'a str>>(s: &'a str) -> IResult<&'a str, Step, E> {
     let (s, cs) = many0(_escape_seq_or_char)(s)?;
     Ok((s, Step::Name(cs.into_iter().collect())))
 }
 
-fn _parse_new_element(s: &str) -> IResult<&str, Step> {
+fn _parse_new_element<'a, E: NomParseError<&'a str>>(s: &'a str) -> IResult<&'a str, Step, E> {
     match char('-')(s) {
         Ok((rest, _)) => {
             if segment_ends(rest) {
@@ -100,34 +396,31 @@ fn _parse_new_element(s: &str) -> IResult<&str, Step> {
     }
 }
 
-fn _escape_seq_or_char(s: &str) -> IResult<&str, char> {
-    let escape_check = _escape_sequence(s);
-    match escape_check {
-        Ok(r) => Ok(r),
-        Err(e) => {
-            match e {
-                // propagate the invalid escape sequence error
-                nom::Err::Error((_, nom::error::ErrorKind::Escaped)) => Err(e),
-                // otherwise this is not an escape sequence
-                _ => none_of("/")(s),
-            }
-        }
+fn _escape_seq_or_char<'a, E: NomParseError<&'a str>>(s: &'a str) -> IResult<&'a str, char, E> {
+    if s.starts_with('~') {
+        // an invalid escape sequence is an error
+        _escape_sequence(s)
+    } else {
+        none_of("/")(s)
     }
 }
 
-fn _escape_sequence(s: &str) -> IResult<&str, char> {
+fn _escape_sequence<'a, E: NomParseError<&'a str>>(s: &'a str) -> IResult<&'a str, char, E> {
     let (s, _) = char('~')(s)?;
-    let (s, l) = anychar(s)?;
+    // a tilde ending the pointer is an unfinished escape sequence, not a literal tilde
+    let (s, l) = anychar::<_, E>(s)
+        .map_err(|_| nom::Err::Error(E::from_error_kind(s, nom::error::ErrorKind::Escaped)))?;
     match l {
         '0' => Ok((s, '~')),
         '1' => Ok((s, '/')),
-        _ => Err(nom::Err::Error(nom::error::make_error(
+        _ => Err(nom::Err::Error(E::from_error_kind(
             s,
             nom::error::ErrorKind::Escaped,
         ))),
     }
 }
 
+#[allow(clippy::iter_nth_zero)]
 fn segment_ends(s: &str) -> bool {
     s.is_empty() || s.chars().nth(0).unwrap() == '/'
 }
@@ -135,6 +428,36 @@ fn segment_ends(s: &str) -> bool {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ParseError {
     pub error: String,
+
+    pub(crate) offset: Option<usize>,
+}
+
+impl ParseError {
+    pub(crate) fn new(error: String) -> Self {
+        Self {
+            error,
+            offset: None,
+        }
+    }
+
+    /// The byte offset into the input where the parsing failed, if known.
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// Renders the input with a caret under the byte where the parsing failed on the second line.
+    /// The input is rendered alone if the offset of the error is not known.
+    pub fn render(&self, input: &str) -> String {
+        match self.offset {
+            Some(offset) => {
+                let column = input
+                    .get(..offset)
+                    .map_or(offset, |before| before.chars().count());
+                format!("{}\n{}^", input, " ".repeat(column))
+            }
+            None => input.to_owned(),
+        }
+    }
 }
 
 impl Display for ParseError {
@@ -145,11 +468,32 @@ impl Display for ParseError {
 
 impl StdError for ParseError {}
 
+/// The parse error with the details about where the parsing failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerboseParseError {
+    /// The context frames of the error as reported by the parser, starting with the innermost
+    /// one. Each frame consists of the byte offset into the input where it applies and the kind of
+    /// the error. There are no frames if the input was incomplete.
+    pub frames: Vec<(usize, VerboseErrorKind)>,
+}
+
+impl Display for VerboseParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.frames.first() {
+            Some((offset, kind)) => f.write_fmt(format_args!(
+                "Invalid JSON Pointer: {:?} at offset {}",
+                kind, offset
+            )),
+            None => f.write_str("Invalid JSON Pointer: Incomplete JSON Pointer"),
+        }
+    }
+}
+
+impl StdError for VerboseParseError {}
+
 impl nom::error::ParseError<&str> for ParseError {
     fn from_error_kind(_: &str, kind: nom::error::ErrorKind) -> Self {
-        Self {
-            error: kind.description().to_owned(),
-        }
+        Self::new(kind.description().to_owned())
     }
 
     fn append(_: &str, _: nom::error::ErrorKind, other: Self) -> Self {
@@ -160,6 +504,14 @@ impl nom::error::ParseError<&str> for ParseError {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_value::Value;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn empty_string() {
+        let p = test_parse("");
+        assert_eq!(0, p.len());
+    }
 
     #[test]
     fn empty_hash() {
@@ -195,6 +547,16 @@ mod tests {
         assert_eq!(Step::Name("007".to_owned()), p[0]);
     }
 
+    #[test]
+    fn digits_followed_by_chars_as_string() {
+        let p = test_parse("/12ab");
+        assert_eq!(Step::Name("12ab".to_owned()), p[0]);
+
+        let p = test_parse("/12ab/3-/4");
+        assert_eq!(Step::Name("3-".to_owned()), p[1]);
+        assert_eq!(Step::Index(4), p[2]);
+    }
+
     #[test]
     fn escape_tilda() {
         let p = test_parse("/a~0/~0b/c~0d");
@@ -219,7 +581,194 @@ mod tests {
         assert!(r.is_err())
     }
 
+    #[test]
+    fn fails_on_unfinished_escape_seq() {
+        let r = parse("/a~");
+        assert!(r.is_err());
+        assert!(parse("/~").is_err());
+        assert!(parse("/a~/b").is_err());
+    }
+
+    #[test]
+    fn uri_fragment() {
+        let p: Vec<Step> = parse_uri_fragment("#/a%20b/~1c%25/0").unwrap().into();
+        assert_eq!(
+            vec![
+                Step::Name("a b".to_owned()),
+                Step::Name("/c%".to_owned()),
+                Step::Index(0)
+            ],
+            p
+        );
+    }
+
+    #[test]
+    fn fails_on_invalid_uri_fragment() {
+        assert!(parse_uri_fragment("/a").is_err());
+        assert!(parse_uri_fragment("#/a%2").is_err());
+        assert!(parse_uri_fragment("#/a%zz").is_err());
+        assert!(parse_uri_fragment("#/a%+1").is_err());
+        assert!(parse_uri_fragment("#/a%ff").is_err());
+    }
+
+    #[test]
+    fn verbose_error_points_at_bad_escape() {
+        let e = parse_verbose("/a~2").unwrap_err();
+        assert!(!e.frames.is_empty());
+        assert_eq!(2, e.frames[0].0);
+    }
+
+    #[test]
+    fn verbose_parse_succeeds() {
+        assert_eq!(parse("/a/0/-").unwrap(), parse_verbose("/a/0/-").unwrap());
+    }
+
+    #[test]
+    fn renders_caret_under_bad_escape() {
+        let e = parse("/a~2").unwrap_err();
+        assert_eq!("/a~2\n  ^", e.render("/a~2"));
+    }
+
+    #[test]
+    fn single_segment() {
+        assert_eq!(Step::NewElement, parse_segment("-").unwrap());
+        assert_eq!(Step::Index(0), parse_segment("0").unwrap());
+        assert_eq!(Step::Name("007".to_owned()), parse_segment("007").unwrap());
+        assert_eq!(Step::Name("a~b".to_owned()), parse_segment("a~0b").unwrap());
+        assert_eq!(Step::Name("".to_owned()), parse_segment("").unwrap());
+        assert!(parse_segment("a/b").is_err());
+        assert!(parse_segment("a~2").is_err());
+    }
+
     fn test_parse(s: &str) -> Vec<Step> {
         parse(s).unwrap().into()
     }
+
+    #[test]
+    fn negative_indices() {
+        let mut arr = BTreeMap::new();
+        arr.insert(
+            Value::String("arr".into()),
+            Value::Seq(vec![Value::U8(1), Value::U8(2), Value::U8(3)]),
+        );
+        let v = Value::Map(arr);
+        let options = ParseOptions::new().negative_indices(true);
+        let find = |p: &str| parse_with(p, &options).unwrap().find(&v).cloned();
+
+        assert_eq!(Some(Value::U8(3)), find("/arr/-1"));
+        assert_eq!(Some(Value::U8(2)), find("/arr/-2"));
+        assert_eq!(None, find("/arr/-4"));
+        assert_eq!(
+            vec![Step::Name("arr".into()), Step::Name("-01".into())],
+            parse_with("/arr/-01", &options).unwrap().steps().to_vec()
+        );
+
+        let strict = parse("/arr/-1").unwrap();
+        assert_eq!(Some(&Step::Name("-1".into())), strict.steps().last());
+        assert!(strict.resolve(&v).is_err());
+    }
+
+    #[test]
+    fn query_value() {
+        assert_eq!("/a/b", parse_query_value("%2Fa%2Fb").unwrap().to_string());
+        assert_eq!(
+            vec![Step::Name("a b".into()), Step::Name("c+d".into())],
+            parse_query_value("%2Fa+b%2Fc%2Bd")
+                .unwrap()
+                .steps()
+                .to_vec()
+        );
+        assert_eq!(Some(4), parse_query_value("%2Fa%2").unwrap_err().offset());
+        assert!(parse_query_value("a%2Fb").is_err());
+    }
+
+    #[test]
+    fn literal_last_segment() {
+        let options = ParseOptions::new().literal_last_segment(true);
+        assert_eq!(parse("/a/b/c"), parse_with("/a/b/c", &options));
+        assert_eq!(parse("/a/0"), parse_with("/a/0", &options));
+        assert_eq!(parse(""), parse_with("", &options));
+
+        assert!(parse("/a/b~").is_err());
+        assert_eq!(
+            vec![Step::Name("a".into()), Step::Name("b~".into())],
+            parse_with("/a/b~", &options).unwrap().steps().to_vec()
+        );
+        assert_eq!(
+            Some(&Step::Name("x~1y".into())),
+            parse_with("/a/x~1y", &options).unwrap().steps().last()
+        );
+        assert!(parse_with("/a~/b", &options).is_err());
+    }
+
+    #[test]
+    fn raw_rest() {
+        assert_eq!(
+            vec![Step::Name("files".into()), Step::Name("a/b~.txt".into())],
+            parse_with_rest("/files/a/b~.txt", 1)
+                .unwrap()
+                .steps()
+                .to_vec()
+        );
+        assert_eq!(parse("/a/b"), parse_with_rest("/a/b", 2));
+        assert_eq!(
+            Some(&Step::Name("b".into())),
+            parse_with_rest("/a/b", 1).unwrap().steps().last()
+        );
+    }
+
+    #[test]
+    fn max_segment_len() {
+        let options = ParseOptions::new().max_segment_len(Some(4));
+        assert_eq!(
+            parse("/abcd/~1~1~1~1"),
+            parse_with("/abcd/~1~1~1~1", &options)
+        );
+        assert_eq!(parse("#/a/1234"), parse_with("#/a/1234", &options));
+
+        let err = parse_with("/a/abcde", &options).unwrap_err();
+        assert_eq!(Some(3), err.offset());
+        assert!(parse_with("#/abcde", &options).is_err());
+        assert!(parse_with("/a/~1~1~1~1~1", &options).is_err());
+        assert!(parse_with("/a/abcde", &ParseOptions::new()).is_ok());
+
+        assert_eq!(parse("abcdefgh"), parse_with("abcdefgh", &options));
+        assert_eq!(parse("#abcdefgh"), parse_with("#abcdefgh", &options));
+        assert_eq!(
+            Some(2),
+            parse_with("#/abcde", &options).unwrap_err().offset()
+        );
+    }
+
+    #[test]
+    fn list() {
+        let list = parse_list(" /a/0  /b~1c\t#/d ").unwrap();
+        assert_eq!(
+            vec!["/a/0", "/b~1c", "/d"],
+            list.iter().map(|p| p.to_string()).collect::<Vec<_>>()
+        );
+        assert_eq!(Ok(Vec::new()), parse_list(""));
+
+        let err = parse_list("/a /b~2 /c").unwrap_err();
+        assert!(err.error.starts_with("Invalid JSON Pointer #1 `/b~2`"));
+        assert_eq!(Some(5), err.offset());
+    }
+
+    #[test]
+    fn trim_trailing_empty() {
+        let mut b = BTreeMap::new();
+        b.insert(Value::String("b".into()), Value::U8(1));
+        let mut a = BTreeMap::new();
+        a.insert(Value::String("a".into()), Value::Map(b));
+        let v = Value::Map(a);
+
+        let options = ParseOptions::new().trim_trailing_empty(true);
+        assert_eq!(parse("/a/b"), parse_with("/a/b/", &options));
+        assert_eq!(
+            Some(&Value::U8(1)),
+            parse_with("/a/b/", &options).unwrap().find(&v)
+        );
+        assert_eq!(None, parse("/a/b/").unwrap().find(&v));
+        assert_eq!(parse("/a/"), parse_with("/a//", &options));
+    }
 }
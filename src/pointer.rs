@@ -15,48 +15,660 @@
  *   limitations under the License.
  */
 
+use crate::compact;
+use crate::digest;
+use crate::intern;
+use crate::journal;
+use crate::lazy::{self, LazyValue};
+use crate::mutate;
 use crate::parser;
+use crate::step_array;
 use crate::traverse;
-use serde_value::Value;
+use serde::Serialize;
+use serde_value::{SerializerError, Value};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::fmt;
+use std::fmt::Display;
 use std::str::FromStr;
+use std::sync::Arc;
 
 /// Represents a pointer to the data as defined in the RFC6901 - the JSON Pointer specification.
-#[derive(Eq, PartialEq, PartialOrd, Debug, Clone)]
+///
+/// The ordering of the pointers compares the steps one by one. It matches the ordering of the
+/// canonical strings of the pointers as long as the names don't need escaping, don't contain
+/// characters sorting before `/` and the indices have the same number of digits. Index steps
+/// always sort after the name steps and the new element steps after both. The wildcards sort after
+/// the new element steps and the indices from the end come last.
+#[derive(Eq, PartialEq, PartialOrd, Ord, Debug, Clone)]
 pub struct Pointer {
     steps: Vec<Step>,
 }
 
 /// Represents a single traversal step of the pointer.
-#[derive(Eq, PartialEq, PartialOrd, Debug, Clone)]
+///
+/// With the `serde` feature, the steps are serialized as an externally tagged enum, so that e.g.
+/// `Step::Index(3)` and `Step::Name("3")` remain distinct in the binary formats.
+#[derive(Eq, PartialEq, PartialOrd, Ord, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Step {
     Name(String),
     Index(usize),
     NewElement,
+
+    /// Matches any single child of a map or a sequence. The parser never produces this step, it can
+    /// only be used in pointers built by hand. Only the methods that explicitly mention wildcards
+    /// expand it, the other traversals don't resolve it. It is displayed as `*` which doesn't parse
+    /// back to a wildcard.
+    Wildcard,
+
+    /// Counts the elements from the end of a sequence, so that `IndexFromEnd(1)` is the last
+    /// element. This is not a part of RFC6901 and is only produced by the parser if enabled using
+    /// `ParseOptions::negative_indices()`. It is displayed as `-1` which the default parser reads
+    /// as a name.
+    IndexFromEnd(usize),
+}
+
+/// Wraps a single step so that it is serialized as its escaped segment string, e.g. `a~1b`, `3` or
+/// `-`, instead of the tagged form used by `Step` itself. Only the steps the default parser
+/// produces survive the round trip, the numeric names come back as indices and the wildcards and
+/// the indices from the end as names.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct SegmentStr(pub Step);
+
+/// Describes how a pointer relates to another pointer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerRelation {
+    /// Both pointers have the same steps.
+    Equal,
+
+    /// The pointer is a proper prefix of the other pointer.
+    Ancestor,
+
+    /// The other pointer is a proper prefix of the pointer.
+    Descendant,
+
+    /// Neither pointer is a prefix of the other. The parameter is the index of the first step that
+    /// differs.
+    Divergent(usize),
+}
+
+/// Describes why a pointer is not structurally well-formed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StructureError {
+    /// The `Step::NewElement` can only be the last step of a pointer. The parameter is the position
+    /// of the offending step.
+    MisplacedNewElement(usize),
 }
 
 pub type ParseError = parser::ParseError;
+pub type VerboseParseError = parser::VerboseParseError;
+pub type ParseOptions = parser::ParseOptions;
+pub type EnsureError = traverse::EnsureError;
+pub type ResolveError = traverse::ResolveError;
+pub type ScalarError = traverse::ScalarError;
+pub type FindResult<'a> = traverse::FindResult<'a>;
+pub type OverlapError = traverse::OverlapError;
+pub type IndexValidationError = traverse::IndexValidationError;
+pub type TraversalStats = traverse::TraversalStats;
+pub type MergeError = mutate::MergeError;
+pub type SetError = mutate::SetError;
+pub type UnflattenError = mutate::UnflattenError;
+pub type CompactError = compact::CompactError;
+pub type StepArrayError = step_array::StepArrayError;
+pub type InternedPointer = intern::InternedPointer;
+pub type InternedStep = intern::InternedStep;
+pub type StringInterner = intern::StringInterner;
+pub type Journal = journal::Journal;
+pub type Operation = journal::Operation;
 pub type ValuePointer<'a> = traverse::ValuePointer<'a>;
 pub type ValuePointerMut<'a> = traverse::ValuePointerMut<'a>;
 
 impl Pointer {
+    /// Builds the pointer from the already split segments, parsing each of them using
+    /// `Step::parse_segment()`.
+    pub fn from_segments<I, S>(segments: I) -> Result<Pointer, ParseError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        segments
+            .into_iter()
+            .map(|s| Step::parse_segment(s.as_ref()))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Pointer::from)
+    }
+
+    /// Parses the pointer like `from_str()` but rejects the inputs longer than `max_len` bytes and
+    /// the pointers with more than `max_steps` steps. Both limits are checked before the parsing
+    /// starts, which makes this suitable for the untrusted input.
+    pub fn from_str_bounded(
+        s: &str,
+        max_len: usize,
+        max_steps: usize,
+    ) -> Result<Pointer, ParseError> {
+        if s.len() > max_len {
+            return Err(ParseError::new(format!(
+                "JSON Pointer longer than {} bytes",
+                max_len
+            )));
+        }
+
+        // every slash separates a step, the slashes in names are escaped
+        if s.matches('/').count() > max_steps {
+            return Err(ParseError::new(format!(
+                "JSON Pointer has more than {} steps",
+                max_steps
+            )));
+        }
+
+        parser::parse(s)
+    }
+
+    /// Represents the pointer as a sequence of single-key maps, each naming the kind of the step,
+    /// e.g. `[{"name": "a"}, {"index": 0}, {"new": true}]`. Unlike the string form, this keeps the
+    /// indices and the numeric names apart.
+    pub fn to_step_array(&self) -> Value {
+        step_array::to_step_array(self)
+    }
+
+    /// Reads the pointer back from the representation produced by `to_step_array()`.
+    pub fn from_step_array(v: &Value) -> Result<Pointer, StepArrayError> {
+        step_array::from_step_array(v)
+    }
+
+    /// Encodes the pointer in a compact binary form. Unlike the string form, this needs no escaping
+    /// and keeps the indices and the numeric names apart. Each step is a tag byte, followed by the
+    /// varint length and the UTF-8 bytes of a name or by the varint index.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        compact::to_bytes(self)
+    }
+
+    /// Decodes the pointer from the compact binary form produced by `to_bytes()`.
+    pub fn from_bytes_compact(bytes: &[u8]) -> Result<Pointer, CompactError> {
+        compact::from_bytes(bytes)
+    }
+
+    /// Escapes the segments and joins them into a pointer string, e.g. `["a/b", "c"]` into
+    /// `/a~1b/c`. Use this instead of concatenating the untrusted keys.
+    ///
+    /// The string parses back into a name step for each segment with
+    /// `ParseOptions::names_only()`. The default parser reads the segment `-` as the new element
+    /// and the digits without a leading zero as indices, so an untrusted key `-` would turn a
+    /// lookup into an append position.
+    pub fn build_escaped(segments: &[&str]) -> String {
+        segments
+            .iter()
+            .map(|segment| format!("/{}", Step::Name((*segment).to_owned())))
+            .collect()
+    }
+
+    /// Builds the pointer consisting of a name step for each of the segments, without any escaping
+    /// or parsing. This is the same pointer as parsing the output of `build_escaped()` with
+    /// `ParseOptions::names_only()`.
+    pub fn from_names(segments: &[&str]) -> Pointer {
+        Pointer::from(
+            segments
+                .iter()
+                .map(|segment| Step::Name((*segment).to_owned()))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Builds the pointer from a key like `server.0.port` or `SERVER_0_PORT`, splitting it on the
+    /// separator. The segments consisting of digits without a leading zero become indices, like in
+    /// the parser, the rest become names as they are. There is no escaping, the empty string is the
+    /// root pointer.
+    pub fn from_dotted(s: &str, separator: char) -> Pointer {
+        if s.is_empty() {
+            return Pointer::default();
+        }
+        let steps = s
+            .split(separator)
+            .map(|segment| {
+                let is_index = !segment.is_empty()
+                    && segment.bytes().all(|b| b.is_ascii_digit())
+                    && (segment == "0" || !segment.starts_with('0'));
+                match segment.parse() {
+                    Ok(idx) if is_index => Step::Index(idx),
+                    _ => Step::Name(segment.to_owned()),
+                }
+            })
+            .collect::<Vec<_>>();
+        Pointer::from(steps)
+    }
+
+    /// Parses several pointers separated by whitespace, e.g. `/a /b/0`. The error message of the
+    /// first pointer that doesn't parse includes its position in the list. As the empty pointer
+    /// cannot be delimited, the root pointer can only be written as `#`.
+    pub fn parse_list(s: &str) -> Result<Vec<Pointer>, ParseError> {
+        parser::parse_list(s)
+    }
+
+    /// Parses the first `fixed_segments` segments of the pointer and takes the rest of it, after
+    /// the next `/`, as a single literal name, slashes included. This is for the pointers ending
+    /// with a raw path, e.g. `/files/docs/report.txt` with one fixed segment refers to the name
+    /// `docs/report.txt` under `files`.
+    ///
+    /// Note that this is not RFC6901. The tail is never unescaped nor parsed as an index.
+    pub fn parse_with_rest(s: &str, fixed_segments: usize) -> Result<Pointer, ParseError> {
+        parser::parse_with_rest(s, fixed_segments)
+    }
+
+    /// Parses the pointer storing the names in the interner, so that the pointers sharing the
+    /// names share their storage, too. This saves memory when keeping many similar pointers.
+    pub fn parse_interned(
+        s: &str,
+        interner: &mut StringInterner,
+    ) -> Result<InternedPointer, ParseError> {
+        intern::parse(s, interner)
+    }
+
+    /// Parses the pointer like `from_str()` with the deviations from RFC6901 enabled in the
+    /// options.
+    pub fn parse_with(s: &str, options: &ParseOptions) -> Result<Pointer, ParseError> {
+        parser::parse_with(s, options)
+    }
+
+    /// Parses the pointer like `from_str()` but on failure returns the structured information about
+    /// where the parsing failed that can be used to render custom diagnostics.
+    pub fn parse_verbose(s: &str) -> Result<Pointer, VerboseParseError> {
+        parser::parse_verbose(s)
+    }
+
+    /// Parses the URI fragment representation of the pointer, e.g. `#/a%20b`. Unlike `from_str()`,
+    /// this requires the leading `#` and percent-decodes the pointer before parsing it.
+    pub fn from_uri_fragment(s: &str) -> Result<Pointer, ParseError> {
+        parser::parse_uri_fragment(s)
+    }
+
+    /// Parses the pointer from the value of a URL query parameter, e.g. `%2Fa%2Fb`. Unlike
+    /// `from_uri_fragment()`, there is no leading `#` and a `+` decodes to a space, as in the
+    /// HTML form encoding.
+    pub fn from_query_value(s: &str) -> Result<Pointer, ParseError> {
+        parser::parse_query_value(s)
+    }
+
     pub fn push(&mut self, step: Step) -> &mut Self {
         self.steps.push(step);
         self
     }
 
+    /// Pushes a name step with the provided name.
+    pub fn push_name(&mut self, name: impl Into<String>) -> &mut Self {
+        self.push(Step::Name(name.into()))
+    }
+
+    /// Pushes a name step taking ownership of the provided buffer, without copying it.
+    ///
+    /// This is an advanced API for tight loops that build the names in reused or pre-allocated
+    /// buffers. It is equivalent to `push_name()` called with a `String`.
+    pub fn push_name_from_buf(&mut self, buf: String) -> &mut Self {
+        self.push(Step::Name(buf))
+    }
+
     pub fn pop(&mut self) -> Option<Step> {
         self.steps.pop()
     }
 
+    /// Removes and returns the first step, shifting the rest of the steps towards the root. This
+    /// is useful for consuming the pointer front to back.
+    pub fn pop_front(&mut self) -> Option<Step> {
+        if self.steps.is_empty() {
+            None
+        } else {
+            Some(self.steps.remove(0))
+        }
+    }
+
+    /// Takes the steps out of the pointer, leaving it empty.
+    pub fn take_steps(&mut self) -> Vec<Step> {
+        std::mem::take(&mut self.steps)
+    }
+
+    /// Splits the pointer before the first wildcard. Returns the pointer without wildcards and
+    /// `None` if there is no wildcard.
+    pub fn split_at_first_wildcard(&self) -> (Pointer, Option<Pointer>) {
+        match self.steps.iter().position(|s| *s == Step::Wildcard) {
+            Some(pos) => (
+                Pointer::from(self.steps[..pos].to_vec()),
+                Some(Pointer::from(self.steps[pos..].to_vec())),
+            ),
+            None => (self.clone(), None),
+        }
+    }
+
+    /// Returns a copy of this pointer with the new element step pushed, i.e. the pointer appending
+    /// to the sequence this pointer points to when used with `set()`.
+    pub fn appending(&self) -> Pointer {
+        let mut pointer = self.clone();
+        pointer.push(Step::NewElement);
+        pointer
+    }
+
+    /// Returns the pointer consisting of the steps of this pointer followed by the steps of the
+    /// other one.
+    pub fn join(&self, other: &Pointer) -> Pointer {
+        let mut steps = Vec::with_capacity(self.steps.len() + other.steps.len());
+        steps.extend_from_slice(&self.steps);
+        steps.extend_from_slice(&other.steps);
+        Pointer::from(steps)
+    }
+
+    /// Returns the pointer consisting of the steps of the prefix followed by the steps of this
+    /// pointer. This is `prefix.join(self)`, useful for mounting a pointer into a sub-document
+    /// under its place in the parent document.
+    pub fn prepend(&self, prefix: &Pointer) -> Pointer {
+        prefix.join(self)
+    }
+
+    /// Replaces all the steps into sequences, i.e. the indices and the new element steps, with
+    /// wildcards, keeping the names. The pointers to the same field of different elements have the
+    /// same shape, e.g. `/items/3/name` and `/items/7/name` both have the shape `/items/*/name`.
+    pub fn shape(&self) -> Pointer {
+        let steps = self
+            .steps
+            .iter()
+            .map(|step| match step {
+                Step::Index(_) | Step::NewElement | Step::IndexFromEnd(_) => Step::Wildcard,
+                step => step.clone(),
+            })
+            .collect::<Vec<_>>();
+        Pointer::from(steps)
+    }
+
+    /// Returns the pointer with each name transformed by the function. The other steps are kept
+    /// as they are.
+    pub fn map_names(&self, mut f: impl FnMut(&str) -> String) -> Pointer {
+        let steps = self
+            .steps
+            .iter()
+            .map(|step| match step {
+                Step::Name(name) => Step::Name(f(name)),
+                step => step.clone(),
+            })
+            .collect::<Vec<_>>();
+        Pointer::from(steps)
+    }
+
+    /// Retains only the steps for which the function returns `true`, like `Vec::retain()`.
+    pub fn retain_steps(&mut self, f: impl FnMut(&Step) -> bool) {
+        self.steps.retain(f)
+    }
+
     pub fn insert(&mut self, index: usize, step: Step) {
         self.steps.insert(index, step)
     }
 
+    /// Like `insert()` but returns the pointer so that the calls can be chained, like with
+    /// `push()`.
+    pub fn insert_and(&mut self, index: usize, step: Step) -> &mut Self {
+        self.insert(index, step);
+        self
+    }
+
     pub fn remove(&mut self, index: usize) -> Step {
         self.steps.remove(index)
     }
 
+    pub(crate) fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// Checks that the pointer is well-formed regardless of the data it is going to be used with.
+    ///
+    /// Pointers obtained by parsing always are, but the ones assembled by hand using `push()` or
+    /// `insert()` might not be.
+    pub fn validate_structure(&self) -> Result<(), StructureError> {
+        let last = self.steps.len().saturating_sub(1);
+        match self.steps.iter().position(|s| *s == Step::NewElement) {
+            Some(pos) if pos < last => Err(StructureError::MisplacedNewElement(pos)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that none of the index steps refers past the end of the sequence it is applied to in
+    /// the provided value. Unlike resolving the pointer, this reports the first offending step.
+    /// The steps following the first one that doesn't resolve are not checked.
+    pub fn validate_indices(&self, root: &Value) -> Result<(), IndexValidationError> {
+        traverse::validate_indices(root, self)
+    }
+
+    /// Returns true if this is the root pointer, i.e. the pointer without any steps. Both the empty
+    /// string and `#` parse to the root pointer.
+    pub fn is_root(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Returns true if the pointer can only be used for reading the data, i.e. it doesn't contain
+    /// the new element step.
+    pub fn is_read_only(&self) -> bool {
+        !self.steps.contains(&Step::NewElement)
+    }
+
+    /// Returns true if the pointer addresses at most a single existing value, i.e. it contains
+    /// neither the new element nor the wildcard steps. The results of resolving such pointers can
+    /// be cached for as long as the data doesn't change. The indices counted from the end are
+    /// considered deterministic, too.
+    pub fn is_deterministic(&self) -> bool {
+        self.steps
+            .iter()
+            .all(|s| !matches!(s, Step::NewElement | Step::Wildcard))
+    }
+
+    /// Returns the key or index the last step of the pointer refers to, as a value. This
+    /// corresponds to the `#` form of the relative JSON pointers.
+    ///
+    /// Returns `None` for the root pointer and for the pointers ending with the new element, the
+    /// wildcard or the index from the end step, because none of them names a single key or index
+    /// on its own.
+    pub fn leaf_token(&self) -> Option<Value> {
+        match self.steps.last() {
+            Some(Step::Name(name)) => Some(Value::String(name.clone())),
+            Some(Step::Index(idx)) => Some(Value::U64(*idx as u64)),
+            _ => None,
+        }
+    }
+
+    /// Returns the RFC6901 string representation of the pointer. This is the stable form suitable
+    /// for keying the pointers, e.g. in a `BTreeMap`.
+    ///
+    /// The string doesn't distinguish all the steps, so the round trip through the parser changes
+    /// the numeric names like `Name("0")` into indices, `Name("-")` into the new element step and
+    /// the wildcards and the indices from the end into the names `*` and `-N`. As keys, such
+    /// pointers collide with the pointers they parse to. Such pointers can only be built by hand,
+    /// the pointers obtained from the default parser round trip exactly.
+    pub fn as_canonical_string(&self) -> String {
+        self.to_string()
+    }
+
+    /// Compares the pointers step by step, skipping the steps at the provided positions. The
+    /// pointers still need to have the same number of steps.
+    pub fn eq_ignoring(&self, other: &Pointer, ignore_positions: &[usize]) -> bool {
+        self.steps.len() == other.steps.len()
+            && self
+                .steps
+                .iter()
+                .zip(other.steps.iter())
+                .enumerate()
+                .all(|(i, (a, b))| a == b || ignore_positions.contains(&i))
+    }
+
+    /// Checks whether the pointers address the same location regardless of how their steps are
+    /// represented. An index step is semantically equal to a name step consisting of the same
+    /// number, because both have the same string form.
+    pub fn semantic_eq(&self, other: &Pointer) -> bool {
+        self.steps.len() == other.steps.len()
+            && self
+                .steps
+                .iter()
+                .zip(other.steps.iter())
+                .all(|(a, b)| match (a, b) {
+                    (Step::Index(i), Step::Name(n)) | (Step::Name(n), Step::Index(i)) => {
+                        *n == i.to_string()
+                    }
+                    _ => a == b,
+                })
+    }
+
+    /// Removes the pointers that are semantically equal (see `semantic_eq()`) to some pointer
+    /// earlier in the vector. The first-seen pointers are kept in their original order.
+    pub fn dedup_canonical(pointers: &mut Vec<Pointer>) {
+        let mut unique: Vec<Pointer> = Vec::with_capacity(pointers.len());
+        for p in pointers.drain(..) {
+            if !unique.iter().any(|u| u.semantic_eq(&p)) {
+                unique.push(p);
+            }
+        }
+        *pointers = unique;
+    }
+
+    /// Checks whether this pointer matches the pattern pointer without resolving it against any
+    /// value. The pointers must have the same number of steps and each step must be equal to the
+    /// step of the pattern, unless it is a wildcard, which matches any single step.
+    pub fn matches_pattern(&self, pattern: &Pointer) -> bool {
+        self.steps.len() == pattern.steps.len()
+            && self
+                .steps
+                .iter()
+                .zip(pattern.steps.iter())
+                .all(|(s, p)| *p == Step::Wildcard || s == p)
+    }
+
+    /// Classifies how this pointer relates to the other pointer.
+    pub fn relationship(&self, other: &Pointer) -> PointerRelation {
+        let common = self
+            .steps
+            .iter()
+            .zip(other.steps.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        if common < self.steps.len() && common < other.steps.len() {
+            PointerRelation::Divergent(common)
+        } else if self.steps.len() == other.steps.len() {
+            PointerRelation::Equal
+        } else if common == self.steps.len() {
+            PointerRelation::Ancestor
+        } else {
+            PointerRelation::Descendant
+        }
+    }
+
+    /// Describes how this pointer relates to the other one in a sentence, e.g. "`/a/b/c` is a
+    /// child of `/a/b`" or "`/a/x` and `/a/y` diverge at segment 1".
+    pub fn explain_relationship(&self, other: &Pointer) -> String {
+        let name = |p: &Pointer| {
+            if p.is_root() {
+                "the root".to_owned()
+            } else {
+                format!("`{}`", p)
+            }
+        };
+        let (this, that) = (name(self), name(other));
+        let depth = |a: &Pointer, b: &Pointer| a.steps.len() - b.steps.len();
+        match self.relationship(other) {
+            PointerRelation::Equal => format!("{} is equal to {}", this, that),
+            PointerRelation::Ancestor if depth(other, self) == 1 => {
+                format!("{} is the parent of {}", this, that)
+            }
+            PointerRelation::Ancestor => format!("{} is an ancestor of {}", this, that),
+            PointerRelation::Descendant if depth(self, other) == 1 => {
+                format!("{} is a child of {}", this, that)
+            }
+            PointerRelation::Descendant => format!("{} is a descendant of {}", this, that),
+            PointerRelation::Divergent(pos) => {
+                format!("{} and {} diverge at segment {}", this, that, pos)
+            }
+        }
+    }
+
+    /// Returns the position of the first step in which the pointers differ, or `None` if one of
+    /// them is a prefix of the other (or they are equal).
+    pub fn first_difference(&self, other: &Pointer) -> Option<usize> {
+        match self.relationship(other) {
+            PointerRelation::Divergent(pos) => Some(pos),
+            _ => None,
+        }
+    }
+
+    /// Checks whether a mutation at the provided pointer may change what this pointer points to.
+    /// That is the case when the mutation is at this pointer or at any of its ancestors.
+    ///
+    /// The pointers diverging at the index steps into the same sequence are reported as affected,
+    /// too, because the insertions and removals shift the following elements. This is a
+    /// conservative over-approximation, the mutations replacing an element in place or at a higher
+    /// index don't actually shift anything. Note that the mutations below this pointer are not
+    /// reported, even though they change the content of the value this pointer points to.
+    pub fn affected_by(&self, mutation: &Pointer) -> bool {
+        let is_index = |step: &Step| match step {
+            Step::Index(_) | Step::NewElement | Step::IndexFromEnd(_) => true,
+            Step::Name(_) | Step::Wildcard => false,
+        };
+        match mutation.relationship(self) {
+            PointerRelation::Equal | PointerRelation::Ancestor => true,
+            PointerRelation::Descendant => false,
+            PointerRelation::Divergent(pos) => {
+                is_index(&self.steps[pos]) && is_index(&mutation.steps[pos])
+            }
+        }
+    }
+
+    /// Returns the path of the pointer as used in the JSON Patch (RFC6902) operations. This is the
+    /// plain string representation without the leading `#`, with the new element rendered as `-`.
+    pub fn to_patch_path(&self) -> String {
+        self.to_string()
+    }
+
+    /// Returns the URI fragment representation of the pointer, e.g. `#/a%20b`. This is the
+    /// canonical string representation, with `~` and `/` escaped, that is then percent-encoded.
+    pub fn to_uri_fragment(&self) -> String {
+        format!("#{}", parser::percent_encode(&self.to_string()))
+    }
+
+    /// Finds the data this pointer points to like `find()`, but if it doesn't exist, the error
+    /// describes how much of the pointer did resolve and which step failed.
+    pub fn resolve<'a>(&self, root: &'a Value) -> Result<&'a Value, ResolveError> {
+        traverse::resolve(root, self)
+    }
+
+    /// Like `resolve()` but reports the pointers ending with the new element step, which never
+    /// point to an existing value, as `ResolveError::NewElementInRead`. This helps to catch the
+    /// pointers meant for writing used for reading.
+    pub fn find_checked<'a>(&self, root: &'a Value) -> Result<&'a Value, ResolveError> {
+        traverse::find_checked(root, self)
+    }
+
+    /// Finds the longest prefix of this pointer that resolves against the provided value and
+    /// returns it together with the value it points to. This is at least the root pointer and the
+    /// root value.
+    pub fn deepest_match<'a>(&self, root: &'a Value) -> (Pointer, &'a Value) {
+        let mut current = root;
+        let mut depth = 0;
+        for (pos, step) in self.steps.iter().enumerate() {
+            let step = traverse::read_step(step, pos + 1 == self.steps.len());
+            match traverse::child(current, &step) {
+                Some(child) => current = child,
+                None => break,
+            }
+            depth += 1;
+        }
+
+        (Pointer::from(self.steps[..depth].to_vec()), current)
+    }
+
     /// Traverses the provided value and finds the data this pointer points to in it, if any.
+    ///
+    /// The names are looked up as the string keys in the maps. Only if there is no such string key,
+    /// `true` and `false` are tried as the bool keys and the numbers as the integer keys. The index
+    /// steps used on a map are looked up the same way as the numeric names, the string key first,
+    /// then the integer keys. The optional values that are present are looked through, so that the
+    /// steps apply to the values inside them.
+    ///
+    /// A new element step used on a map anywhere but at the end of the pointer is looked up as the
+    /// `"-"` key, so that `/-/x` reaches into such a key. As the last step, it keeps referring to a
+    /// new element, so `/-` doesn't point to an existing value even if the map has the key.
     pub fn traverse<'a>(&self, val: &'a Value) -> Option<ValuePointer<'a>> {
         traverse::traverse(val, self)
     }
@@ -79,6 +691,544 @@ impl Pointer {
             _ => None,
         }
     }
+
+    /// A fast path for the pointers consisting of a single index step. Returns the element of the
+    /// sequence at that index, bypassing the general traversal. Returns `None` for any other
+    /// pointer or if the value is not a sequence.
+    pub fn get_seq_elem_mut<'a>(&self, root: &'a mut Value) -> Option<&'a mut Value> {
+        match (self.steps.as_slice(), root) {
+            ([Step::Index(index)], Value::Seq(seq)) => seq.get_mut(*index),
+            _ => None,
+        }
+    }
+
+    /// Finds the sequence a pointer ending with the new element step points into, so that new
+    /// elements can be pushed or inserted into it directly.
+    ///
+    /// Returns `None` if the pointer doesn't end with the new element step or if it doesn't point
+    /// into an existing sequence.
+    pub fn new_element_slot<'a>(&self, root: &'a mut Value) -> Option<&'a mut Vec<Value>> {
+        match self.traverse_mut(root) {
+            Some(ValuePointerMut::NewUnder(Value::Seq(seq), _)) => Some(seq),
+            _ => None,
+        }
+    }
+
+    /// Finds the data this pointer points to and if it is a string that is itself a valid pointer,
+    /// follows it, repeating until a value that is not a pointer is found. The strings parsing to
+    /// the root pointer, i.e. `""` and `"#"`, are not followed and are returned as they are.
+    ///
+    /// At most `max_hops` indirections are followed. This protects against the cycles - if the
+    /// found value is still a pointer after `max_hops` indirections, `None` is returned. `None` is
+    /// also returned if any string in the chain looks like a pointer but doesn't resolve.
+    pub fn resolve_chain<'a>(&self, root: &'a Value, max_hops: usize) -> Option<&'a Value> {
+        let mut found = self.find(root)?;
+        let mut hops = 0;
+        loop {
+            let next = match found {
+                Value::String(s) => Pointer::from_str(s).ok().filter(|p| !p.is_root()),
+                _ => None,
+            };
+
+            match next {
+                Some(_) if hops == max_hops => return None,
+                Some(p) => found = p.find(root)?,
+                None => return Some(found),
+            }
+            hops += 1;
+        }
+    }
+
+    /// Finds the value this pointer points to, behaving as if the missing values were null. The
+    /// found values are borrowed, a missing value, whether it's the last key or any of the steps
+    /// before it, is returned as an owned `Value::Unit`.
+    pub fn find_or_null<'a>(&self, root: &'a Value) -> Cow<'a, Value> {
+        match self.find(root) {
+            Some(val) => Cow::Borrowed(val),
+            None => Cow::Owned(Value::Unit),
+        }
+    }
+
+    /// Walks the path of this pointer, yielding each value along it with the prefix of the pointer
+    /// addressing it, starting with the root and ending with the value this pointer points to.
+    /// Stops early if the rest of the pointer cannot be followed.
+    pub fn iter_resolved<'a, 'p>(
+        &'p self,
+        root: &'a Value,
+    ) -> impl Iterator<Item = (Pointer, &'a Value)> + 'p
+    where
+        'a: 'p,
+    {
+        let mut current = Some(root);
+        let mut depth = 0;
+        std::iter::from_fn(move || {
+            let val = current?;
+            current = self
+                .steps
+                .get(depth)
+                .map(|step| traverse::read_step(step, depth + 1 == self.steps.len()))
+                .and_then(|step| traverse::child(val, &step));
+            depth += 1;
+            Some((Pointer::from(self.steps[..depth - 1].to_vec()), val))
+        })
+    }
+
+    /// Lazily finds the value this pointer points to in each of the rows.
+    pub fn find_over<'a, 'p>(
+        &'p self,
+        rows: &'a [Value],
+    ) -> impl Iterator<Item = Option<&'a Value>> + 'p
+    where
+        'a: 'p,
+    {
+        rows.iter().map(move |row| self.find(row))
+    }
+
+    /// Finds the value in the overrides and falls back to the base value if the overrides don't
+    /// have it. This allows layering a sparse document over a complete one. Note that a null in
+    /// the overrides is a hit, it doesn't fall back to the base.
+    pub fn find_with_overrides<'a>(
+        &self,
+        base: &'a Value,
+        overrides: &'a Value,
+    ) -> Option<&'a Value> {
+        self.find(overrides).or_else(|| self.find(base))
+    }
+
+    /// Finds the value this pointer points to in the first of the layers containing it. The layers
+    /// are ordered from the most to the least specific, e.g. the local settings, then the profile
+    /// and then the defaults.
+    pub fn find_in_layers<'a>(&self, layers: &'a [Value]) -> Option<&'a Value> {
+        layers.iter().find_map(|layer| self.find(layer))
+    }
+
+    /// Rewrites the steps to the kinds matching the values they are applied to: the numeric names
+    /// used on sequences become indices and the indices used on maps become names. The pointers
+    /// normalized against the same value can be compared directly. The steps after the first one
+    /// that doesn't resolve are kept as they are.
+    pub fn normalized_against(&self, root: &Value) -> Pointer {
+        traverse::normalized(root, self)
+    }
+
+    /// Converts the name steps applied to sequences to indices if the names are canonical indices,
+    /// i.e. digits without a leading zero. This repairs the pointers assembled from the sources
+    /// that don't distinguish names from indices. Unlike `normalized_against()`, the index steps
+    /// applied to maps are kept.
+    pub fn canonicalize_indices(&self, root: &Value) -> Pointer {
+        traverse::canonicalize_indices(root, self)
+    }
+
+    /// Finds the value this pointer points to, retrying the names that are not found with their
+    /// aliases. Each name is aliased at most once, the aliases of the aliases are not followed.
+    pub fn find_with_aliases<'a>(
+        &self,
+        root: &'a Value,
+        aliases: &HashMap<String, String>,
+    ) -> Option<&'a Value> {
+        traverse::find_with_aliases(root, self, aliases)
+    }
+
+    /// Finds the value this pointer points to, matching the names to the map keys regardless of
+    /// their case. A key matching exactly is preferred, otherwise the first matching key in the
+    /// key order is used.
+    ///
+    /// By default, only the ASCII letters are compared case-insensitively. The `unicode-case`
+    /// feature enables the full Unicode case folding, so that e.g. `STRASSE` matches `Straße`.
+    /// This pulls in the `unicase` crate and makes the comparisons of the non-ASCII names slower.
+    pub fn find_case_insensitive<'a>(&self, root: &'a Value) -> Option<&'a Value> {
+        traverse::find_case_insensitive(root, self)
+    }
+
+    /// Finds the value like `find()` but follows every step into all the children it could match
+    /// rather than the first one. A numeric name matches both the string key and the integer keys
+    /// of a map as well as an element of a sequence. This reveals the values the lenient lookups
+    /// could confuse.
+    pub fn find_ambiguous<'a>(&self, root: &'a Value) -> FindResult<'a> {
+        traverse::find_ambiguous(root, self)
+    }
+
+    /// Finds the value this pointer points to, requiring it to be a scalar, i.e. neither a map nor
+    /// a sequence.
+    pub fn find_scalar<'a>(&self, root: &'a Value) -> Result<&'a Value, ScalarError> {
+        traverse::find_scalar(root, self)
+    }
+
+    /// Finds the value this pointer points to, treating the null values as missing. Both the unit
+    /// value and the absent optional value are null.
+    pub fn find_non_null<'a>(&self, root: &'a Value) -> Option<&'a Value> {
+        match self.find(root)? {
+            Value::Unit | Value::Option(None) => None,
+            val => Some(val),
+        }
+    }
+
+    /// Computes a hash of the content of the value this pointer points to, or returns `None` if
+    /// it doesn't point to any value. The equal values hash the same regardless of where they
+    /// are, and because the maps are ordered by their keys, the order of insertion doesn't matter.
+    ///
+    /// The hash is stable across the runs and the platforms, so it can be persisted, but it is not
+    /// a cryptographic digest.
+    pub fn subtree_hash(&self, root: &Value) -> Option<u64> {
+        self.find(root).map(digest::hash)
+    }
+
+    /// Returns the label of the type of the value this pointer points to, e.g. `"map"`, `"seq"`,
+    /// `"string"`, `"i64"` or `"null"`, or `None` if the pointer doesn't point to any value. The
+    /// labels are named after the `Value` variants and are stable.
+    pub fn value_type(&self, root: &Value) -> Option<&'static str> {
+        self.find(root).map(traverse::type_name)
+    }
+
+    /// Finds the value in a lazily materialized source. Only the nodes along the path of this
+    /// pointer are asked for, so the rest of the source is never loaded.
+    pub fn find_lazy<V: LazyValue>(&self, root: V) -> Option<V> {
+        lazy::traverse(root, self)
+    }
+
+    /// Returns the name of the variant if this pointer points to an externally tagged enum, i.e.
+    /// a map with a single string key like `{"Variant": payload}`. The payload itself can be
+    /// reached by appending the variant name to the pointer.
+    ///
+    /// This is the shape the enums take when deserialized into a `Value` from a self-describing
+    /// format like JSON. The serializer of `serde_value` itself doesn't record the variant names.
+    /// Note that a struct with a single field looks the same and is reported too.
+    pub fn enum_variant<'a>(&self, root: &'a Value) -> Option<&'a str> {
+        match self.find(root)? {
+            Value::Map(m) if m.len() == 1 => match m.keys().next() {
+                Some(Value::String(variant)) => Some(variant),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Finds the bytes this pointer points to. Only the `Value::Bytes` leaves are accepted, the
+    /// sequences of numbers are not converted.
+    pub fn get_bytes<'a>(&self, root: &'a Value) -> Option<&'a [u8]> {
+        match self.find(root)? {
+            Value::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// Finds the string this pointer points to. Apart from the string values, this also accepts
+    /// the bytes that are valid UTF-8.
+    pub fn get_str_lossy(&self, root: &Value) -> Option<String> {
+        match self.find(root)? {
+            Value::String(s) => Some(s.clone()),
+            Value::Bytes(b) => String::from_utf8(b.clone()).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the pointers to the direct children of the map or the sequence this pointer points
+    /// to. The map children are returned in the key order. An empty vector is returned if the
+    /// pointer doesn't point to a map or a sequence.
+    pub fn children(&self, root: &Value) -> Vec<Pointer> {
+        match self.find(root) {
+            Some(val) => traverse::child_steps(val)
+                .into_iter()
+                .map(|step| {
+                    let mut child = self.clone();
+                    child.push(step);
+                    child
+                })
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Like `children()` but the children of a map are returned in the provided order of the keys.
+    /// The keys missing from the order follow in the key order. The children of a sequence are
+    /// always returned in the index order.
+    pub fn children_ordered(&self, root: &Value, order: &[&str]) -> Vec<Pointer> {
+        let mut children = self.children(root);
+        children.sort_by_key(|child| {
+            let position = match child.steps.last() {
+                Some(Step::Name(name)) => order.iter().position(|o| o == name),
+                _ => None,
+            };
+            position.unwrap_or(order.len())
+        });
+        children
+    }
+
+    /// Suggests the possible completions of the last segment of a partially typed pointer. The
+    /// part before the last `/` is parsed and resolved against the value and the segments of its
+    /// children that start with the text after the last `/` are returned in their escaped form.
+    ///
+    /// No suggestions are returned if the partial pointer doesn't start with `/` or if its prefix
+    /// doesn't parse or resolve.
+    pub fn complete(partial: &str, root: &Value) -> Vec<String> {
+        let last_slash = match partial.rfind('/') {
+            Some(i) => i,
+            None => return Vec::new(),
+        };
+        let (prefix, typed) = (&partial[..last_slash], &partial[last_slash + 1..]);
+        match Pointer::from_str(prefix).ok().and_then(|p| p.find(root)) {
+            Some(val) => traverse::child_steps(val)
+                .into_iter()
+                .map(|step| step.to_string())
+                .filter(|segment| segment.starts_with(typed))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Finds the pointer to the target value inside the root value. The target is identified by its
+    /// address, not by comparing the values, so it has to be borrowed from the root. Of several
+    /// equal values, only the one actually borrowed is found. Returns `None` if the target is not
+    /// a part of the root or is under a map key that cannot be represented as a name.
+    pub fn of<'a>(root: &'a Value, target: &'a Value) -> Option<Pointer> {
+        let mut path = Vec::new();
+        if traverse::path_of(root, target, &mut path) {
+            Some(Pointer::from(path))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the pointers to all the occurrences of the key in the maps anywhere in the provided
+    /// value, e.g. for auditing where a `password` appears. Unlike the wildcards, the key is looked
+    /// for at any depth. The pointers are ordered depth-first.
+    pub fn find_key_anywhere(root: &Value, key: &str) -> Vec<Pointer> {
+        let mut found = Vec::new();
+        traverse::find_key_anywhere(root, key, &mut Vec::new(), &mut found);
+        found
+    }
+
+    /// Returns the pointers to all the scalar (i.e. not a map or a sequence) values in the provided
+    /// value, in the key order for maps and the index order for sequences.
+    pub fn leaves(root: &Value) -> Vec<Pointer> {
+        let mut leaves = Vec::new();
+        traverse::walk_leaves(root, false, &mut Vec::new(), &mut |path, _| {
+            leaves.push(Pointer::from(path.to_vec()))
+        });
+        leaves
+    }
+
+    /// Pairs the pointers to all the scalar values in the provided value with the values. The pairs
+    /// are ordered the same way as `leaves()` - map keys sorted, sequence indices ascending.
+    ///
+    /// Unlike `leaves()`, the empty maps and sequences are included as well, so that `unflatten()`
+    /// can restore them.
+    pub fn flatten(root: &Value) -> Vec<(Pointer, &Value)> {
+        let mut pairs = Vec::new();
+        traverse::walk_leaves(root, true, &mut Vec::new(), &mut |path, val| {
+            pairs.push((Pointer::from(path.to_vec()), val))
+        });
+        pairs
+    }
+
+    /// Rebuilds a value from the pointers and the values at them, the inverse of `flatten()`.
+    ///
+    /// The missing maps and sequences are created along the way using `ensure_path()`, so the
+    /// indices into each sequence need to come in the ascending order. An empty map is returned if
+    /// there are no pairs.
+    pub fn unflatten<I: IntoIterator<Item = (Pointer, Value)>>(
+        pairs: I,
+    ) -> Result<Value, UnflattenError> {
+        mutate::unflatten(pairs)
+    }
+
+    /// Makes sure the path described by this pointer exists in the provided value and returns the
+    /// value at the end of it.
+    ///
+    /// The existing values are left untouched. Only the missing ones are created - maps for name
+    /// steps, sequences for index and new element steps and a null value at the end of the path.
+    pub fn ensure_path<'a>(&self, root: &'a mut Value) -> Result<&'a mut Value, EnsureError> {
+        traverse::ensure_path(root, self)
+    }
+
+    /// Finds the data this pointer points to in the provided value, expanding the wildcards, and
+    /// returns it along with the concrete pointer that addresses it.
+    ///
+    /// The wildcards are expanded to the first child (maps in the key order, sequences in the index
+    /// order) under which the rest of the pointer resolves. The concrete pointer consists of the
+    /// names of the matched keys and the indices of the matched elements, so e.g. `/0` used on a
+    /// map resolves to the name `0`.
+    pub fn find_resolved<'a>(&self, root: &'a Value) -> Option<(&'a Value, Pointer)> {
+        let mut resolved = Vec::with_capacity(self.steps.len());
+        traverse::find_resolved(root, &self.steps, &mut resolved).map(|v| (v, resolved.into()))
+    }
+
+    /// Finds the value this pointer points to like `find_first()` and reports how much work it
+    /// took. This is meant for profiling the traversals over large documents, not for the hot
+    /// paths.
+    pub fn find_profiled<'a>(&self, root: &'a Value) -> (Option<&'a Value>, TraversalStats) {
+        traverse::find_profiled(root, self)
+    }
+
+    /// Finds the first value matching this pointer, expanding the wildcards in the same order as
+    /// `find_all_resolved()`. The traversal stops at the first match, so this is cheaper than
+    /// taking the first of all the matches and doesn't build the concrete pointer like
+    /// `find_resolved()`.
+    pub fn find_first<'a>(&self, root: &'a Value) -> Option<&'a Value> {
+        traverse::find_first(root, &self.steps)
+    }
+
+    /// Finds all the values matching this pointer, expanding each wildcard to every child under
+    /// which the rest of the pointer resolves. Each value is returned with the concrete pointer
+    /// addressing it, in the key order for maps and the index order for sequences.
+    pub fn find_all_resolved<'a>(&self, root: &'a Value) -> Vec<(Pointer, &'a Value)> {
+        let mut found = Vec::new();
+        traverse::find_all_resolved(root, &self.steps, &mut Vec::new(), &mut found);
+        found
+    }
+
+    /// Sets the value this pointer points to and returns the previous value, if any.
+    ///
+    /// The parent of the value must already exist. A missing key is inserted into a map, the new
+    /// element step or the index right after the last element appends to a sequence.
+    pub fn set(&self, root: &mut Value, value: Value) -> Result<Option<Value>, SetError> {
+        mutate::set(root, self, value)
+    }
+
+    /// Finds the values the pointers point to, borrowing all of them mutably at the same time. The
+    /// values are returned in the order of the pointers.
+    ///
+    /// The values must not overlap, i.e. no two pointers may point to the same value and no value
+    /// may contain another one. This is checked by descending into the values rather than by
+    /// comparing the pointers, so that e.g. an index step and a numeric name addressing the same
+    /// map key are found to overlap, too.
+    pub fn find_many_mut<'a>(
+        pointers: &[Pointer],
+        root: &'a mut Value,
+    ) -> Result<Vec<&'a mut Value>, OverlapError> {
+        traverse::find_many_mut(root, pointers)
+    }
+
+    /// Finds the value this pointer points to or, if only the last name is missing from an
+    /// existing map, inserts the default value under it. Unlike `ensure_path()`, no missing
+    /// parents are created. Returns `None` in all the other cases.
+    pub fn find_mut_or_insert<'a>(
+        &self,
+        root: &'a mut Value,
+        default: Value,
+    ) -> Option<&'a mut Value> {
+        mutate::find_mut_or_insert(root, self, default)
+    }
+
+    /// Sets the string this pointer points to, behaving like `set()` with a `Value::String`. If
+    /// the current value is a string already, it is overwritten in place, reusing its allocation.
+    /// Unlike `set()`, this doesn't return the previous value, because that would need a copy.
+    pub fn set_str(&self, root: &mut Value, value: &str) -> Result<(), SetError> {
+        mutate::set_str(root, self, value)
+    }
+
+    /// Like `set()` but creates the missing parents of the value and, unlike `set()`, also
+    /// overwrites the existing parents that are not of the type the pointer needs.
+    ///
+    /// This is destructive. E.g. setting `/a/b` when `/a` holds a string replaces the string with a
+    /// map and setting `/a/0` when `/a` holds a map replaces the whole map with a sequence.
+    pub fn set_force(&self, root: &mut Value, value: Value) -> Result<Option<Value>, SetError> {
+        mutate::set_force(root, self, value)
+    }
+
+    /// Removes the value this pointer points to and returns it, if it exists. The root value cannot
+    /// be removed.
+    pub fn remove_value(&self, root: &mut Value) -> Option<Value> {
+        mutate::remove(root, self)
+    }
+
+    /// Appends the value to the sequence this pointer points to.
+    pub fn append(&self, root: &mut Value, value: Value) -> Result<(), SetError> {
+        mutate::append(root, self, value)
+    }
+
+    /// Like `set()` but records the operation undoing the change in the journal.
+    pub fn set_journaled(
+        &self,
+        root: &mut Value,
+        value: Value,
+        journal: &mut Journal,
+    ) -> Result<Option<Value>, SetError> {
+        journal::set(root, self, value, journal)
+    }
+
+    /// Like `remove_value()` but records the operation undoing the change in the journal.
+    pub fn remove_journaled(&self, root: &mut Value, journal: &mut Journal) -> Option<Value> {
+        journal::remove(root, self, journal)
+    }
+
+    /// Like `append()` but records the operation undoing the change in the journal.
+    pub fn append_journaled(
+        &self,
+        root: &mut Value,
+        value: Value,
+        journal: &mut Journal,
+    ) -> Result<(), SetError> {
+        journal::append(root, self, value, journal)
+    }
+
+    /// Deep-merges the data at the `src` pointer into the data at the `dst` pointer within the same
+    /// document. The maps are merged key by key, any other values from the source overwrite the
+    /// ones in the destination.
+    pub fn merge_into(src: &Pointer, dst: &Pointer, root: &mut Value) -> Result<(), MergeError> {
+        mutate::merge_into(src, dst, root)
+    }
+
+    /// Finds the data this pointer points to in the value shared behind an `Arc`. The returned
+    /// reference borrows from the `Arc` and so cannot outlive it.
+    pub fn find_arc<'a>(&self, val: &'a Arc<Value>) -> Option<&'a Value> {
+        self.find(val.as_ref())
+    }
+
+    /// Converts the provided data into a `Value` and finds the data this pointer points to in it.
+    /// The found value is cloned out of the converted data.
+    pub fn find_in<T: Serialize>(&self, data: &T) -> Result<Option<Value>, SerializerError> {
+        let val = serde_value::to_value(data)?;
+        Ok(self.find(&val).cloned())
+    }
+}
+
+impl Step {
+    /// Parses a single segment of a pointer, without the leading `/`, using the same escaping and
+    /// the same rules for distinguishing the indices and the new element from the names as the
+    /// pointer parser.
+    pub fn parse_segment(s: &str) -> Result<Step, ParseError> {
+        parser::parse_segment(s)
+    }
+
+    /// Creates an index step, rejecting `usize::MAX`. That index is reserved as a sentinel, because
+    /// it never addresses an element and is most likely the result of an overflow.
+    pub fn checked_index(n: usize) -> Option<Step> {
+        if n == usize::MAX {
+            None
+        } else {
+            Some(Step::Index(n))
+        }
+    }
+
+    /// Returns the unescaped name of a name step.
+    pub fn as_name(&self) -> Option<&str> {
+        match self {
+            Step::Name(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Returns the index of an index step. The indices counted from the end are not included.
+    pub fn as_index(&self) -> Option<usize> {
+        match self {
+            Step::Index(idx) => Some(*idx),
+            _ => None,
+        }
+    }
+
+    /// Returns true for the new element step, written as `-`.
+    pub fn is_new_element(&self) -> bool {
+        *self == Step::NewElement
+    }
+}
+
+/// Parses the pointer and finds the data it points to in the provided value in a single pass.
+///
+/// This gives the same results as `Pointer::from_str()` followed by `find()` but doesn't allocate
+/// the steps of the pointer. At most a single string buffer is allocated and reused for all the
+/// names in the pointer. Invalid pointers don't resolve to anything.
+pub fn resolve_str<'a>(pointer: &str, val: &'a Value) -> Option<&'a Value> {
+    traverse::resolve_str(pointer, val)
 }
 
 impl FromStr for Pointer {
@@ -89,12 +1239,77 @@ impl FromStr for Pointer {
     }
 }
 
+impl Display for Pointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for step in &self.steps {
+            f.write_fmt(format_args!("/{}", step))?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Name(name) => {
+                for c in name.chars() {
+                    match c {
+                        '~' => f.write_str("~0")?,
+                        '/' => f.write_str("~1")?,
+                        _ => f.write_fmt(format_args!("{}", c))?,
+                    }
+                }
+                Ok(())
+            }
+            Step::Index(idx) => f.write_fmt(format_args!("{}", idx)),
+            Step::NewElement => f.write_str("-"),
+            Step::Wildcard => f.write_str("*"),
+            Step::IndexFromEnd(idx) => f.write_fmt(format_args!("-{}", idx)),
+        }
+    }
+}
+
+/// With the `serde` feature, the pointers are serialized as their string representation. Note that
+/// the wildcards don't survive the round trip.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Pointer {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Pointer {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Pointer::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SegmentStr {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SegmentStr {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Step::parse_segment(&s)
+            .map(SegmentStr)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
 impl From<Vec<Step>> for Pointer {
     fn from(ps: Vec<Step>) -> Self {
         Self { steps: ps }
     }
 }
 
+#[allow(clippy::from_over_into)]
 impl Into<Vec<Step>> for Pointer {
     fn into(self) -> Vec<Step> {
         self.steps
@@ -110,6 +1325,7 @@ impl IntoIterator for Pointer {
     }
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for Pointer {
     fn default() -> Self {
         Self {
@@ -117,3 +1333,1130 @@ impl Default for Pointer {
         }
     }
 }
+
+impl Display for StructureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StructureError::MisplacedNewElement(pos) => f.write_fmt(format_args!(
+                "Invalid JSON Pointer: new element step at position {} is not the last step",
+                pos
+            )),
+        }
+    }
+}
+
+impl StdError for StructureError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::map;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    struct Outer {
+        a: u32,
+        b: Inner,
+    }
+
+    #[derive(Serialize)]
+    struct Inner {
+        c: String,
+    }
+
+    fn outer() -> Outer {
+        Outer {
+            a: 1,
+            b: Inner {
+                c: "value".to_owned(),
+            },
+        }
+    }
+
+    #[test]
+    fn parsed_pointer_has_valid_structure() {
+        let p = Pointer::from_str("/a/b/-").unwrap();
+        assert_eq!(Ok(()), p.validate_structure());
+    }
+
+    #[test]
+    fn new_element_in_middle_is_invalid_structure() {
+        let mut p = Pointer::default();
+        p.push(Step::Name("a".to_owned()))
+            .push(Step::NewElement)
+            .push(Step::Name("b".to_owned()));
+
+        assert_eq!(
+            Err(StructureError::MisplacedNewElement(1)),
+            p.validate_structure()
+        );
+    }
+
+    #[test]
+    fn finds_in_serializable_data() {
+        let p = Pointer::from_str("/b/c").unwrap();
+        assert_eq!(
+            Some(Value::String("value".to_owned())),
+            p.find_in(&outer()).unwrap()
+        );
+    }
+
+    #[test]
+    fn finds_nothing_in_serializable_data() {
+        let p = Pointer::from_str("/b/d").unwrap();
+        assert_eq!(None, p.find_in(&outer()).unwrap());
+    }
+
+    #[test]
+    fn leaf_token() {
+        let token = |s| Pointer::from_str(s).unwrap().leaf_token();
+
+        assert_eq!(Some(Value::String("b".to_owned())), token("/a/b"));
+        assert_eq!(Some(Value::U64(2)), token("/a/2"));
+        assert_eq!(None, token("/a/-"));
+        assert_eq!(None, token(""));
+        assert_eq!(
+            None,
+            Pointer::from(vec![Step::Name("a".into()), Step::Wildcard]).leaf_token()
+        );
+        assert_eq!(
+            None,
+            Pointer::from(vec![Step::Name("a".into()), Step::IndexFromEnd(1)]).leaf_token()
+        );
+    }
+
+    #[test]
+    fn canonical_string() {
+        let p = Pointer::from(vec![
+            Step::Name("a/b".to_owned()),
+            Step::Index(0),
+            Step::Name("c~".to_owned()),
+            Step::NewElement,
+        ]);
+
+        assert_eq!("/a~1b/0/c~0/-", p.as_canonical_string());
+        assert_eq!(p, Pointer::from_str(&p.as_canonical_string()).unwrap());
+        assert_eq!("", Pointer::default().as_canonical_string());
+
+        let mut named = Pointer::default();
+        named.push_name("0").push_name("-");
+        assert_eq!("/0/-", named.as_canonical_string());
+        assert_ne!(
+            named,
+            Pointer::from_str(&named.as_canonical_string()).unwrap()
+        );
+    }
+
+    #[test]
+    fn ordering_matches_canonical_strings() {
+        let mut pointers: Vec<Pointer> = vec!["/c/1", "/a/b", "/b/c/d", "/a", "/b", "/c/0", "/c"]
+            .into_iter()
+            .map(|s| Pointer::from_str(s).unwrap())
+            .collect();
+        let mut strings: Vec<String> = pointers.iter().map(|p| p.as_canonical_string()).collect();
+
+        pointers.sort();
+        strings.sort();
+
+        let sorted: Vec<String> = pointers.iter().map(|p| p.as_canonical_string()).collect();
+        assert_eq!(strings, sorted);
+    }
+
+    #[test]
+    fn read_only() {
+        assert!(Pointer::from_str("/a/b").unwrap().is_read_only());
+        assert!(!Pointer::from_str("/a/-").unwrap().is_read_only());
+    }
+
+    #[test]
+    fn deterministic() {
+        let det = |p| Pointer::from_str(p).unwrap().is_deterministic();
+        assert!(det("/a/0/b"));
+        assert!(det(""));
+        assert!(!det("/a/-"));
+        assert!(!Pointer::from(vec![Step::Name("a".into()), Step::Wildcard]).is_deterministic());
+    }
+
+    #[test]
+    fn relationship() {
+        let rel = |a, b| {
+            Pointer::from_str(a)
+                .unwrap()
+                .relationship(&Pointer::from_str(b).unwrap())
+        };
+
+        assert_eq!(PointerRelation::Equal, rel("/a/0", "/a/0"));
+        assert_eq!(PointerRelation::Ancestor, rel("/a", "/a/0/b"));
+        assert_eq!(PointerRelation::Ancestor, rel("", "/a"));
+        assert_eq!(PointerRelation::Descendant, rel("/a/0/b", "/a"));
+        assert_eq!(PointerRelation::Divergent(1), rel("/a/x/c", "/a/y"));
+        assert_eq!(PointerRelation::Divergent(0), rel("/0", "/a"));
+    }
+
+    #[test]
+    fn display_doesnt_percent_encode() {
+        let p = Pointer::from(vec![Step::Name("a%/b".to_owned())]);
+        assert_eq!("/a%~1b", p.to_string());
+    }
+
+    #[test]
+    fn uri_fragment_escapes_before_percent_encoding() {
+        let p = Pointer::from(vec![Step::Name("a%/b c".to_owned()), Step::Index(1)]);
+        assert_eq!("#/a%25~1b%20c/1", p.to_uri_fragment());
+        assert_eq!(p, Pointer::from_uri_fragment(&p.to_uri_fragment()).unwrap());
+    }
+
+    #[test]
+    fn new_element_slot() {
+        let mut val = Value::Map(
+            vec![(Value::String("a".into()), Value::Seq(vec![Value::I32(1)]))]
+                .into_iter()
+                .collect(),
+        );
+
+        Pointer::from_str("/a/-")
+            .unwrap()
+            .new_element_slot(&mut val)
+            .unwrap()
+            .push(Value::I32(2));
+
+        assert_eq!(
+            Some(&Value::Seq(vec![Value::I32(1), Value::I32(2)])),
+            Pointer::from_str("/a").unwrap().find(&val)
+        );
+        assert!(Pointer::from_str("/a")
+            .unwrap()
+            .new_element_slot(&mut val)
+            .is_none());
+    }
+
+    #[test]
+    fn insert_and_chains() {
+        let mut p = Pointer::from_str("/b").unwrap();
+        p.insert_and(0, Step::Name("a".into()))
+            .insert_and(2, Step::Index(0));
+        assert_eq!(Pointer::from_str("/a/b/0").unwrap(), p);
+    }
+
+    #[test]
+    fn push_name_from_buf() {
+        let mut buf = String::with_capacity(16);
+        buf.push_str("a/b");
+        let buf_ptr = buf.as_ptr();
+
+        let mut p = Pointer::default();
+        p.push_name_from_buf(buf);
+
+        let mut expected = Pointer::default();
+        expected.push_name("a/b");
+
+        assert_eq!(expected, p);
+        match &p.steps[0] {
+            Step::Name(name) => assert_eq!(buf_ptr, name.as_ptr()),
+            _ => panic!("Should have pushed a name"),
+        }
+    }
+
+    #[test]
+    fn resolves_chain() {
+        let val = Value::Map(
+            vec![
+                (Value::String("a".into()), Value::String("/b".into())),
+                (Value::String("b".into()), Value::String("/c".into())),
+                (Value::String("c".into()), Value::I32(42)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let p = Pointer::from_str("/a").unwrap();
+
+        assert_eq!(Some(&Value::I32(42)), p.resolve_chain(&val, 2));
+        assert_eq!(None, p.resolve_chain(&val, 1));
+    }
+
+    #[test]
+    fn doesnt_follow_chain_to_root() {
+        let val = Value::Map(
+            vec![
+                (Value::String("empty".into()), Value::String("".into())),
+                (Value::String("hash".into()), Value::String("#".into())),
+                (Value::String("dangling".into()), Value::String("/x".into())),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let chain = |p| Pointer::from_str(p).unwrap().resolve_chain(&val, 5);
+
+        assert_eq!(Some(&Value::String("".into())), chain("/empty"));
+        assert_eq!(Some(&Value::String("#".into())), chain("/hash"));
+        assert_eq!(None, chain("/dangling"));
+    }
+
+    #[test]
+    fn cuts_off_chain_cycle() {
+        let val = Value::Map(
+            vec![
+                (Value::String("a".into()), Value::String("/b".into())),
+                (Value::String("b".into()), Value::String("/a".into())),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        assert_eq!(
+            None,
+            Pointer::from_str("/a").unwrap().resolve_chain(&val, 10)
+        );
+    }
+
+    #[test]
+    fn take_steps() {
+        let mut p = Pointer::from_str("/a/0").unwrap();
+
+        let steps = p.take_steps();
+
+        assert_eq!(vec![Step::Name("a".to_owned()), Step::Index(0)], steps);
+        assert_eq!(Pointer::default(), p);
+    }
+
+    #[test]
+    fn gets_str_from_string_or_bytes() {
+        let val = Value::Map(
+            vec![
+                (Value::String("s".into()), Value::String("text".into())),
+                (Value::String("b".into()), Value::Bytes(b"text".to_vec())),
+                (Value::String("x".into()), Value::Bytes(vec![0xff, 0xfe])),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let get = |s| Pointer::from_str(s).unwrap().get_str_lossy(&val);
+
+        assert_eq!(Some("text".to_owned()), get("/s"));
+        assert_eq!(get("/s"), get("/b"));
+        assert_eq!(None, get("/x"));
+    }
+
+    #[test]
+    fn children() {
+        let val = Value::Map(
+            vec![(
+                Value::String("a".into()),
+                Value::Map(
+                    vec![
+                        (Value::String("y".into()), Value::I32(1)),
+                        (Value::String("x".into()), Value::Seq(vec![Value::I32(2)])),
+                    ]
+                    .into_iter()
+                    .collect(),
+                ),
+            )]
+            .into_iter()
+            .collect(),
+        );
+        let children = |s| Pointer::from_str(s).unwrap().children(&val);
+
+        assert_eq!(
+            vec![
+                Pointer::from_str("/a/x").unwrap(),
+                Pointer::from_str("/a/y").unwrap()
+            ],
+            children("/a")
+        );
+        assert_eq!(vec![Pointer::from_str("/a/x/0").unwrap()], children("/a/x"));
+        assert!(children("/a/y").is_empty());
+        assert!(children("/b").is_empty());
+    }
+
+    #[test]
+    fn children_ordered() {
+        let val = Value::Map(
+            vec!["a", "b", "c", "d"]
+                .into_iter()
+                .map(|k| (Value::String(k.into()), Value::Unit))
+                .collect(),
+        );
+
+        let children: Vec<String> = Pointer::default()
+            .children_ordered(&val, &["c", "x", "a"])
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+
+        assert_eq!(vec!["/c", "/a", "/b", "/d"], children);
+    }
+
+    #[test]
+    fn parses_bounded() {
+        assert_eq!(
+            Pointer::from_str("/a/b").unwrap(),
+            Pointer::from_str_bounded("/a/b", 4, 2).unwrap()
+        );
+        assert_eq!(
+            None,
+            Pointer::from_str_bounded("/a/b", 3, 2)
+                .unwrap_err()
+                .offset()
+        );
+        assert_eq!(
+            None,
+            Pointer::from_str_bounded("/a/b", 4, 1)
+                .unwrap_err()
+                .offset()
+        );
+        assert!(Pointer::from_str_bounded("/a~2", 4, 1).is_err());
+    }
+
+    #[test]
+    fn patch_path() {
+        assert_eq!(
+            "/arr/-",
+            Pointer::from_str("#/arr/-").unwrap().to_patch_path()
+        );
+        assert_eq!(
+            "/a~1b",
+            Pointer::from(vec![Step::Name("a/b".to_owned())]).to_patch_path()
+        );
+    }
+
+    #[test]
+    fn finds_in_arc() {
+        let val = Arc::new(Value::Seq(vec![Value::I32(1), Value::I32(2)]));
+        let found = Pointer::from_str("/1").unwrap().find_arc(&val);
+        assert_eq!(Some(&Value::I32(2)), found);
+    }
+
+    #[test]
+    fn from_segments() {
+        assert_eq!(
+            Pointer::from_str("/a/0/-/a~0b").unwrap(),
+            Pointer::from_segments(["a", "0", "-", "a~0b"]).unwrap()
+        );
+        assert!(Pointer::from_segments(vec!["a".to_owned(), "b/c".to_owned()]).is_err());
+    }
+
+    #[test]
+    fn root() {
+        assert!(Pointer::from_str("").unwrap().is_root());
+        assert!(Pointer::from_str("#").unwrap().is_root());
+        assert!(!Pointer::from_str("/").unwrap().is_root());
+    }
+
+    #[test]
+    fn gets_seq_elem_mut() {
+        let mut val = Value::Seq(vec![Value::I32(0), Value::I32(1), Value::I32(2)]);
+
+        *Pointer::from_str("/2")
+            .unwrap()
+            .get_seq_elem_mut(&mut val)
+            .unwrap() = Value::I32(42);
+        assert_eq!(
+            Value::Seq(vec![Value::I32(0), Value::I32(1), Value::I32(42)]),
+            val
+        );
+
+        assert!(Pointer::from_str("/a/2")
+            .unwrap()
+            .get_seq_elem_mut(&mut val)
+            .is_none());
+    }
+
+    #[test]
+    fn value_type() {
+        let val = Value::Map(
+            vec![
+                (Value::String("a".into()), Value::Map(BTreeMap::new())),
+                (Value::String("n".into()), Value::Unit),
+                (Value::String("i".into()), Value::I64(1)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let value_type = |s| Pointer::from_str(s).unwrap().value_type(&val);
+
+        assert_eq!(Some("map"), value_type("/a"));
+        assert_eq!(Some("null"), value_type("/n"));
+        assert_eq!(Some("i64"), value_type("/i"));
+        assert_eq!(None, value_type("/missing"));
+    }
+
+    fn nested() -> Value {
+        Value::Map(
+            vec![
+                (
+                    Value::String("a/b".into()),
+                    Value::Seq(vec![
+                        Value::I32(1),
+                        Value::Map(
+                            vec![(Value::String("c".into()), Value::Bool(true))]
+                                .into_iter()
+                                .collect(),
+                        ),
+                    ]),
+                ),
+                (Value::String("d".into()), Value::String("x".into())),
+                (Value::String("e".into()), Value::Map(BTreeMap::new())),
+            ]
+            .into_iter()
+            .collect(),
+        )
+    }
+
+    #[test]
+    fn leaves() {
+        let leaves: Vec<String> = Pointer::leaves(&nested())
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+
+        assert_eq!(vec!["/a~1b/0", "/a~1b/1/c", "/d"], leaves);
+        assert_eq!(
+            Step::Name("a/b".to_owned()),
+            Pointer::leaves(&nested())[0].steps[0]
+        );
+    }
+
+    #[test]
+    fn flatten() {
+        let val = nested();
+        let flat: Vec<(String, &Value)> = Pointer::flatten(&val)
+            .into_iter()
+            .map(|(p, v)| (p.to_string(), v))
+            .collect();
+
+        assert_eq!(
+            vec![
+                ("/a~1b/0".to_owned(), &Value::I32(1)),
+                ("/a~1b/1/c".to_owned(), &Value::Bool(true)),
+                ("/d".to_owned(), &Value::String("x".into())),
+                ("/e".to_owned(), &Value::Map(BTreeMap::new())),
+            ],
+            flat
+        );
+    }
+
+    #[test]
+    fn flatten_keeps_empty_containers() {
+        let val = Value::Map(
+            vec![
+                (Value::String("a".into()), Value::Map(BTreeMap::new())),
+                (Value::String("b".into()), Value::I32(1)),
+                (
+                    Value::String("c".into()),
+                    Value::Seq(vec![Value::Seq(vec![])]),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        let flat = Pointer::flatten(&val);
+        assert_eq!(3, flat.len());
+        assert_eq!(
+            Pointer::leaves(&val),
+            vec![Pointer::from_str("/b").unwrap()]
+        );
+        let pairs = flat.into_iter().map(|(p, v)| (p, v.clone()));
+        assert_eq!(Ok(val.clone()), Pointer::unflatten(pairs));
+
+        let empty = Value::Seq(vec![]);
+        let pairs: Vec<_> = Pointer::flatten(&empty)
+            .into_iter()
+            .map(|(p, v)| (p, v.clone()))
+            .collect();
+        assert_eq!(Ok(empty), Pointer::unflatten(pairs));
+    }
+
+    #[test]
+    fn matches_pattern() {
+        let pattern = Pointer::from(vec![
+            Step::Name("items".to_owned()),
+            Step::Wildcard,
+            Step::Name("name".to_owned()),
+        ]);
+        let matches = |s| Pointer::from_str(s).unwrap().matches_pattern(&pattern);
+
+        assert!(matches("/items/3/name"));
+        assert!(matches("/items/x/name"));
+        assert!(!matches("/items/3"));
+        assert!(!matches("/items/3/name/x"));
+        assert!(!matches("/items/3/id"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn steps_round_trip_bincode() {
+        let p = Pointer::from_str("/3/a/3/-").unwrap();
+        let steps: Vec<Step> = p.clone().into();
+
+        let bytes = bincode::serialize(&steps).unwrap();
+        let deserialized: Vec<Step> = bincode::deserialize(&bytes).unwrap();
+
+        assert_eq!(steps, deserialized);
+        assert_ne!(
+            bincode::serialize(&Step::Index(3)).unwrap(),
+            bincode::serialize(&Step::Name("3".to_owned())).unwrap()
+        );
+
+        let bytes = bincode::serialize(&p).unwrap();
+        assert_eq!(p, bincode::deserialize::<Pointer>(&bytes).unwrap());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn segment_str_round_trip() {
+        for (step, s) in [
+            (Step::Name("a/b~".into()), "a~1b~0"),
+            (Step::Index(3), "3"),
+            (Step::NewElement, "-"),
+        ] {
+            let v = serde_value::to_value(SegmentStr(step.clone())).unwrap();
+            assert_eq!(Value::String(s.into()), v);
+            assert_eq!(SegmentStr(step), v.deserialize_into().unwrap());
+        }
+        assert!(Value::String("~2".into())
+            .deserialize_into::<SegmentStr>()
+            .is_err());
+    }
+
+    #[test]
+    fn deepest_match() {
+        let val = nested();
+
+        let (prefix, found) = Pointer::from_str("/a~1b/1/missing/x")
+            .unwrap()
+            .deepest_match(&val);
+        assert_eq!(Pointer::from_str("/a~1b/1").unwrap(), prefix);
+        assert_eq!(
+            Pointer::from_str("/a~1b/1").unwrap().find(&val),
+            Some(found)
+        );
+
+        let (prefix, found) = Pointer::from_str("/x").unwrap().deepest_match(&val);
+        assert!(prefix.is_root());
+        assert_eq!(&val, found);
+    }
+
+    #[test]
+    fn enum_variant() {
+        // the shape of `{"shape": Shape::Circle { radius: 3 }, "name": "c"}` deserialized from JSON
+        let mut circle = BTreeMap::new();
+        circle.insert(Value::String("radius".into()), Value::U32(3));
+        let mut shape = BTreeMap::new();
+        shape.insert(Value::String("Circle".into()), Value::Map(circle));
+        let mut drawing = BTreeMap::new();
+        drawing.insert(Value::String("shape".into()), Value::Map(shape));
+        drawing.insert(Value::String("name".into()), Value::String("c".into()));
+        let v = Value::Map(drawing);
+
+        let shape = Pointer::from_str("/shape").unwrap();
+        assert_eq!(Some("Circle"), shape.enum_variant(&v));
+        assert_eq!(
+            Some(&Value::U32(3)),
+            Pointer::from_str("/shape/Circle/radius").unwrap().find(&v)
+        );
+        assert_eq!(None, Pointer::from_str("").unwrap().enum_variant(&v));
+        assert_eq!(None, Pointer::from_str("/name").unwrap().enum_variant(&v));
+    }
+
+    #[test]
+    fn pop_front() {
+        let mut p = Pointer::from_str("/a/b/c").unwrap();
+        assert_eq!(Some(Step::Name("a".into())), p.pop_front());
+        assert_eq!("/b/c", p.to_string());
+        assert_eq!(Some(Step::Name("b".into())), p.pop_front());
+        assert_eq!(Some(Step::Name("c".into())), p.pop_front());
+        assert!(p.is_root());
+        assert_eq!(None, p.pop_front());
+    }
+
+    #[test]
+    fn dedup_canonical() {
+        let mut named = Pointer::default();
+        named.push_name("a").push_name("0");
+        let indexed = Pointer::from_str("/a/0").unwrap();
+        assert_ne!(named, indexed);
+        assert!(named.semantic_eq(&indexed));
+        assert!(!named.semantic_eq(&Pointer::from_str("/a/1").unwrap()));
+
+        let mut pointers = vec![
+            indexed.clone(),
+            Pointer::from_str("/b").unwrap(),
+            named,
+            indexed,
+        ];
+        Pointer::dedup_canonical(&mut pointers);
+        assert_eq!(
+            vec!["/a/0", "/b"],
+            pointers.iter().map(|p| p.to_string()).collect::<Vec<_>>()
+        );
+        assert_eq!(Step::Index(0), pointers[0].steps()[1]);
+    }
+
+    #[test]
+    fn find_non_null() {
+        let mut map = BTreeMap::new();
+        map.insert(Value::String("null".into()), Value::Unit);
+        map.insert(Value::String("none".into()), Value::Option(None));
+        map.insert(Value::String("value".into()), Value::I32(1));
+        let v = Value::Map(map);
+
+        let find = |p: &str| Pointer::from_str(p).unwrap().find_non_null(&v);
+        assert_eq!(None, find("/null"));
+        assert_eq!(None, find("/none"));
+        assert_eq!(None, find("/absent"));
+        assert_eq!(Some(&Value::I32(1)), find("/value"));
+    }
+
+    #[test]
+    fn complete() {
+        let v = nested();
+        assert_eq!(vec!["a~1b", "d", "e"], Pointer::complete("/", &v));
+        assert_eq!(vec!["a~1b"], Pointer::complete("/a", &v));
+        assert_eq!(vec!["0", "1"], Pointer::complete("/a~1b/", &v));
+        assert_eq!(vec!["c"], Pointer::complete("/a~1b/1/", &v));
+        assert!(Pointer::complete("/e/", &v).is_empty());
+        assert!(Pointer::complete("/x/", &v).is_empty());
+        assert!(Pointer::complete("a", &v).is_empty());
+    }
+
+    #[test]
+    fn find_with_overrides() {
+        let base = nested();
+        let mut overrides = BTreeMap::new();
+        overrides.insert(Value::String("d".into()), Value::String("y".into()));
+        let overrides = Value::Map(overrides);
+
+        let find = |p: &str| {
+            Pointer::from_str(p)
+                .unwrap()
+                .find_with_overrides(&base, &overrides)
+        };
+        assert_eq!(Some(&Value::String("y".into())), find("/d"));
+        assert_eq!(Some(&Value::I32(1)), find("/a~1b/0"));
+        assert_eq!(None, find("/x"));
+    }
+
+    #[test]
+    fn find_in_layers() {
+        let layer = |k: &str, v: &str| {
+            let mut map = BTreeMap::new();
+            map.insert(Value::String(k.into()), Value::String(v.into()));
+            Value::Map(map)
+        };
+        let layers = vec![
+            layer("a", "local"),
+            layer("b", "profile"),
+            layer("b", "default"),
+        ];
+
+        let find = |p: &str| Pointer::from_str(p).unwrap().find_in_layers(&layers);
+        assert_eq!(Some(&Value::String("profile".into())), find("/b"));
+        assert_eq!(Some(&Value::String("local".into())), find("/a"));
+        assert_eq!(None, find("/c"));
+        assert_eq!(None, Pointer::from_str("/b").unwrap().find_in_layers(&[]));
+    }
+
+    #[test]
+    fn step_accessors() {
+        let name = Step::Name("a".into());
+        let index = Step::Index(3);
+        let new = Step::NewElement;
+
+        assert_eq!(Some("a"), name.as_name());
+        assert_eq!(None, index.as_name());
+        assert_eq!(None, new.as_name());
+
+        assert_eq!(None, name.as_index());
+        assert_eq!(Some(3), index.as_index());
+        assert_eq!(None, new.as_index());
+
+        assert!(!name.is_new_element());
+        assert!(!index.is_new_element());
+        assert!(new.is_new_element());
+    }
+
+    #[test]
+    fn find_or_null() {
+        let v = nested();
+        let find = |p: &str| Pointer::from_str(p).unwrap().find_or_null(&v);
+
+        assert!(matches!(find("/d"), Cow::Borrowed(Value::String(s)) if s == "x"));
+        assert!(matches!(find("/e/missing"), Cow::Owned(Value::Unit)));
+        assert!(matches!(find("/x/y"), Cow::Owned(Value::Unit)));
+    }
+
+    #[test]
+    fn eq_ignoring() {
+        let a = Pointer::from_str("/a/3/c").unwrap();
+        let b = Pointer::from_str("/a/7/c").unwrap();
+        assert!(a.eq_ignoring(&b, &[1]));
+        assert!(!a.eq_ignoring(&b, &[0, 2]));
+        assert!(!a.eq_ignoring(&Pointer::from_str("/a/7").unwrap(), &[1, 2]));
+    }
+
+    #[test]
+    fn affected_by() {
+        let read = Pointer::from_str("/items/3/name").unwrap();
+        let affected = |p: &str| read.affected_by(&Pointer::from_str(p).unwrap());
+
+        assert!(affected("/items/3/name"));
+        assert!(affected("/items"));
+        assert!(affected(""));
+        assert!(affected("/items/1"));
+        assert!(affected("/items/-"));
+        assert!(!affected("/items/3/name/first"));
+        assert!(!affected("/items/3/id"));
+        assert!(!affected("/other"));
+    }
+
+    #[test]
+    fn retain_steps() {
+        let mut p = Pointer::from_str("/a/0/b/-").unwrap();
+        p.retain_steps(|step| matches!(step, Step::Name(_)));
+        assert_eq!("/a/b", p.to_string());
+    }
+
+    #[test]
+    fn find_over() {
+        let row = |name: Option<&str>| {
+            let mut map = BTreeMap::new();
+            map.insert(Value::String("id".into()), Value::U8(1));
+            if let Some(name) = name {
+                map.insert(Value::String("name".into()), Value::String(name.into()));
+            }
+            Value::Map(map)
+        };
+        let rows = vec![row(Some("a")), row(None), row(Some("c"))];
+
+        let names = Pointer::from_str("/name")
+            .unwrap()
+            .find_over(&rows)
+            .map(|v| v.cloned())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                Some(Value::String("a".into())),
+                None,
+                Some(Value::String("c".into()))
+            ],
+            names
+        );
+    }
+
+    #[test]
+    fn first_difference() {
+        let p = |s: &str| Pointer::from_str(s).unwrap();
+        assert_eq!(Some(1), p("/a/b/c").first_difference(&p("/a/x/c")));
+        assert_eq!(Some(0), p("/a").first_difference(&p("/b/c")));
+        assert_eq!(None, p("/a/b").first_difference(&p("/a/b/c")));
+        assert_eq!(None, p("/a/b/c").first_difference(&p("/a")));
+        assert_eq!(None, p("/a").first_difference(&p("/a")));
+    }
+
+    #[test]
+    fn checked_index() {
+        assert_eq!(Some(Step::Index(0)), Step::checked_index(0));
+        assert_eq!(Some(Step::Index(41)), Step::checked_index(41));
+        assert_eq!(None, Step::checked_index(usize::MAX));
+        assert_eq!(None, Step::checked_index(0usize.wrapping_sub(1)));
+    }
+
+    #[test]
+    fn of() {
+        let v = Value::Seq(vec![nested(), nested()]);
+        let second = match &v {
+            Value::Seq(seq) => &seq[1],
+            _ => unreachable!(),
+        };
+        let target = Pointer::from_str("/a~1b/1/c")
+            .unwrap()
+            .find(second)
+            .unwrap();
+
+        assert_eq!(
+            Some("/1/a~1b/1/c".to_owned()),
+            Pointer::of(&v, target).map(|p| p.to_string())
+        );
+        assert_eq!(Some(Pointer::default()), Pointer::of(&v, &v));
+        assert_eq!(None, Pointer::of(&v, &Value::Bool(true)));
+    }
+
+    #[test]
+    fn normalized_against() {
+        let v = nested();
+        let mut named = Pointer::default();
+        named.push_name("a/b").push_name("1").push(Step::Index(0));
+        let normalized = named.normalized_against(&v);
+
+        assert_eq!(
+            vec![
+                Step::Name("a/b".into()),
+                Step::Index(1),
+                Step::Name("0".into())
+            ],
+            normalized.steps().to_vec()
+        );
+        assert_eq!(
+            normalized,
+            Pointer::from_str("/a~1b/1/0")
+                .unwrap()
+                .normalized_against(&v)
+        );
+
+        let mut unresolved = Pointer::default();
+        unresolved.push_name("x").push_name("1");
+        assert_eq!(unresolved, unresolved.normalized_against(&v));
+    }
+
+    #[test]
+    fn canonicalize_indices() {
+        let items = Value::Seq((0..6).map(Value::U8).collect());
+        let root = Value::Map(
+            vec![
+                (Value::String("items".into()), items),
+                (Value::String("m".into()), Value::Map(BTreeMap::new())),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        let mut p = Pointer::default();
+        p.push_name("items").push_name("5");
+        let canonical = p.canonicalize_indices(&root);
+        assert_eq!(
+            vec![Step::Name("items".into()), Step::Index(5)],
+            canonical.steps().to_vec()
+        );
+        assert_eq!(Some(&Value::U8(5)), canonical.find(&root));
+
+        let mut p = Pointer::default();
+        p.push_name("items").push_name("05");
+        assert_eq!(p, p.canonicalize_indices(&root));
+
+        let p = Pointer::from(vec![Step::Name("m".into()), Step::Index(0)]);
+        assert_eq!(p, p.canonicalize_indices(&root));
+    }
+
+    #[test]
+    fn split_at_first_wildcard() {
+        let p = Pointer::from(vec![
+            Step::Name("items".into()),
+            Step::Wildcard,
+            Step::Name("tags".into()),
+            Step::Wildcard,
+        ]);
+        let (prefix, rest) = p.split_at_first_wildcard();
+        assert_eq!("/items", prefix.to_string());
+        assert_eq!(Some("/*/tags/*".to_owned()), rest.map(|r| r.to_string()));
+
+        let p = Pointer::from_str("/a/0").unwrap();
+        assert_eq!((p.clone(), None), p.split_at_first_wildcard());
+    }
+
+    #[test]
+    fn iter_resolved() {
+        let v = nested();
+        let walk = |p: &str| {
+            Pointer::from_str(p)
+                .unwrap()
+                .iter_resolved(&v)
+                .map(|(p, v)| (p.to_string(), v))
+                .collect::<Vec<_>>()
+        };
+
+        let path = walk("/a~1b/1");
+        assert_eq!(
+            vec!["", "/a~1b", "/a~1b/1"],
+            path.iter().map(|(p, _)| p.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(&v, path[0].1);
+        assert_eq!(
+            Pointer::from_str("/a~1b/1").unwrap().find(&v),
+            Some(path[2].1)
+        );
+
+        assert_eq!(2, walk("/d/x/y").len());
+    }
+
+    #[test]
+    fn find_key_anywhere() {
+        let secret = || Value::String("secret".into());
+        let root = map(vec![
+            ("password", secret()),
+            (
+                "users",
+                Value::Seq(vec![
+                    map(vec![("name", Value::Unit)]),
+                    map(vec![("password", secret())]),
+                ]),
+            ),
+        ]);
+
+        assert_eq!(
+            vec![
+                Pointer::from_str("/password").unwrap(),
+                Pointer::from_str("/users/1/password").unwrap()
+            ],
+            Pointer::find_key_anywhere(&root, "password")
+        );
+        assert!(Pointer::find_key_anywhere(&root, "token").is_empty());
+    }
+
+    #[test]
+    fn build_escaped() {
+        let s = Pointer::build_escaped(&["users", "a/b", "~x"]);
+        assert_eq!("/users/a~1b/~0x", s);
+        assert_eq!(
+            vec![
+                Step::Name("users".into()),
+                Step::Name("a/b".into()),
+                Step::Name("~x".into())
+            ],
+            Pointer::from_str(&s).unwrap().steps().to_vec()
+        );
+        assert_eq!("", Pointer::build_escaped(&[]));
+
+        // the new element and the indices come back as names only with the option
+        let s = Pointer::build_escaped(&["-", "0", "12", "-1"]);
+        assert_eq!("/-/0/12/-1", s);
+        let names_only = ParseOptions::new().names_only(true).negative_indices(true);
+        assert_eq!(
+            Pointer::from_names(&["-", "0", "12", "-1"]),
+            Pointer::parse_with(&s, &names_only).unwrap()
+        );
+        assert_eq!(
+            vec![Step::NewElement, Step::Index(0)],
+            Pointer::from_str("/-/0").unwrap().steps().to_vec()
+        );
+        let s = Pointer::build_escaped(&["-", "0"]);
+        assert_eq!(
+            vec![Step::Name("-".into()), Step::Name("0".into())],
+            Pointer::from_names(&["-", "0"]).steps().to_vec()
+        );
+        assert_eq!(s, Pointer::from_names(&["-", "0"]).to_string());
+    }
+
+    #[test]
+    fn from_dotted() {
+        assert_eq!(
+            vec![
+                Step::Name("server".into()),
+                Step::Index(0),
+                Step::Name("port".into())
+            ],
+            Pointer::from_dotted("server.0.port", '.').steps().to_vec()
+        );
+        assert_eq!(
+            vec![
+                Step::Name("SERVER".into()),
+                Step::Name("01".into()),
+                Step::Name("a/b".into())
+            ],
+            Pointer::from_dotted("SERVER_01_a/b", '_').steps().to_vec()
+        );
+        assert!(Pointer::from_dotted("", '.').is_root());
+    }
+
+    #[test]
+    fn prepend() {
+        let port = Pointer::from_str("/port").unwrap();
+        let web = Pointer::from_str("/servers/web").unwrap();
+        assert_eq!("/servers/web/port", port.prepend(&web).to_string());
+        assert_eq!("/servers/web/port", web.join(&port).to_string());
+        assert_eq!(port, port.prepend(&Pointer::default()));
+        assert_eq!(web, Pointer::default().prepend(&web));
+    }
+
+    #[test]
+    fn get_bytes() {
+        let mut map = BTreeMap::new();
+        map.insert(Value::String("b".into()), Value::Bytes(vec![0, 255]));
+        map.insert(Value::String("s".into()), Value::String("ab".into()));
+        let v = Value::Map(map);
+
+        let get = |p: &str| Pointer::from_str(p).unwrap().get_bytes(&v);
+        assert_eq!(Some(&[0u8, 255][..]), get("/b"));
+        assert_eq!(None, get("/s"));
+        assert_eq!(None, get("/x"));
+    }
+
+    #[test]
+    fn shape() {
+        let a = Pointer::from_str("/items/3/name").unwrap().shape();
+        let b = Pointer::from_str("/items/7/name").unwrap().shape();
+        assert_eq!(a, b);
+        assert_eq!("/items/*/name", a.to_string());
+        assert_eq!(Some(&Step::Wildcard), a.steps().get(1));
+        assert_eq!(
+            "/a/*/*",
+            Pointer::from_str("/a/0/-").unwrap().shape().to_string()
+        );
+    }
+
+    #[test]
+    fn appending() {
+        let arr = Pointer::from_str("/arr").unwrap();
+        let appending = arr.appending();
+        assert_eq!("/arr/-", appending.to_string());
+        assert_eq!("/arr", arr.to_string());
+
+        let mut map = BTreeMap::new();
+        map.insert(Value::String("arr".into()), Value::Seq(vec![]));
+        let mut v = Value::Map(map);
+        appending.set(&mut v, Value::I32(1)).unwrap();
+        assert_eq!(Some(&Value::Seq(vec![Value::I32(1)])), arr.find(&v));
+    }
+
+    #[test]
+    fn subtree_hash() {
+        let mut map = BTreeMap::new();
+        map.insert(Value::String("x".into()), nested());
+        map.insert(Value::String("y".into()), nested());
+        let v = Value::Map(map);
+
+        let hash = |p: &str| Pointer::from_str(p).unwrap().subtree_hash(&v);
+        assert!(hash("/x").is_some());
+        assert_eq!(hash("/x"), hash("/y"));
+        assert_eq!(hash("/x/e"), hash("/y/e"));
+        assert_ne!(hash("/x"), hash("/x/a~1b"));
+        assert_ne!(hash("/x/a~1b/0"), hash("/x/d"));
+        assert_eq!(None, hash("/z"));
+    }
+
+    #[test]
+    fn map_names() {
+        let p = Pointer::from_str("/a/0/b/-").unwrap();
+        assert_eq!("/A/0/B/-", p.map_names(|n| n.to_uppercase()).to_string());
+
+        let mut first = true;
+        let tenant = p.map_names(|n| {
+            let prefixed = if first {
+                format!("t1.{}", n)
+            } else {
+                n.to_owned()
+            };
+            first = false;
+            prefixed
+        });
+        assert_eq!("/t1.a/0/b/-", tenant.to_string());
+    }
+
+    #[test]
+    fn explain_relationship() {
+        let explain = |a: &str, b: &str| {
+            Pointer::from_str(a)
+                .unwrap()
+                .explain_relationship(&Pointer::from_str(b).unwrap())
+        };
+        assert_eq!("`/a/b/c` is a child of `/a/b`", explain("/a/b/c", "/a/b"));
+        assert_eq!("`/a/b/c` is a descendant of `/a`", explain("/a/b/c", "/a"));
+        assert_eq!("`/a` is the parent of `/a/b`", explain("/a", "/a/b"));
+        assert_eq!("the root is an ancestor of `/a/b`", explain("", "/a/b"));
+        assert_eq!("`/a/0` is equal to `/a/0`", explain("/a/0", "/a/0"));
+        assert_eq!(
+            "`/a/x` and `/a/y` diverge at segment 1",
+            explain("/a/x", "/a/y")
+        );
+    }
+}
@@ -0,0 +1,59 @@
+/*
+*   Copyright (c) 2019 Lukas Krejci
+*   All rights reserved.
+
+*   Licensed under the Apache License, Version 2.0 (the "License");
+*   you may not use this file except in compliance with the License.
+*   You may obtain a copy of the License at
+
+*   http://www.apache.org/licenses/LICENSE-2.0
+
+*   Unless required by applicable law or agreed to in writing, software
+*   distributed under the License is distributed on an "AS IS" BASIS,
+*   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*   See the License for the specific language governing permissions and
+*   limitations under the License.
+*/
+
+//! The shortcuts for the one-off lookups. Import everything with
+//! `use serde_pointer::prelude::*;`.
+
+pub use crate::pointer::{Pointer, Step};
+
+use serde_value::Value;
+use std::str::FromStr;
+
+/// Parses the pointer and finds the value it points to, like `serde_json::Value::pointer()`.
+/// Returns `None` if the pointer doesn't parse.
+pub fn get<'a>(root: &'a Value, pointer: &str) -> Option<&'a Value> {
+    Pointer::from_str(pointer).ok()?.find(root)
+}
+
+/// Parses the pointer and finds the value it points to for modification, like
+/// `serde_json::Value::pointer_mut()`. Returns `None` if the pointer doesn't parse.
+pub fn get_mut<'a>(root: &'a mut Value, pointer: &str) -> Option<&'a mut Value> {
+    Pointer::from_str(pointer).ok()?.find_mut(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_and_get_mut() {
+        let mut v = Value::Map(
+            vec![(Value::String("a".into()), Value::Seq(vec![Value::I32(1)]))]
+                .into_iter()
+                .collect(),
+        );
+
+        assert_eq!(Some(&Value::I32(1)), get(&v, "/a/0"));
+        assert_eq!(None, get(&v, "/a/1"));
+        assert_eq!(None, get(&v, "a/0"));
+        assert_eq!(None, get(&v, "/a~"));
+
+        *get_mut(&mut v, "/a/0").unwrap() = Value::I32(2);
+        assert_eq!(Some(&Value::I32(2)), get(&v, "/a/0"));
+        assert!(get_mut(&mut v, "a").is_none());
+    }
+}
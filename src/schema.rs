@@ -0,0 +1,63 @@
+/*
+*   Copyright (c) 2019 Lukas Krejci
+*   All rights reserved.
+
+*   Licensed under the Apache License, Version 2.0 (the "License");
+*   you may not use this file except in compliance with the License.
+*   You may obtain a copy of the License at
+
+*   http://www.apache.org/licenses/LICENSE-2.0
+
+*   Unless required by applicable law or agreed to in writing, software
+*   distributed under the License is distributed on an "AS IS" BASIS,
+*   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*   See the License for the specific language governing permissions and
+*   limitations under the License.
+*/
+
+//! Helpers for working with JSON Schema documents.
+
+use crate::pointer::Pointer;
+use serde_value::Value;
+
+/// Resolves the JSON Schema `$ref` pointing into the same document, like `#/definitions/Address`,
+/// and returns the referenced subschema.
+///
+/// References to other documents and references that are not valid URI fragment pointers don't
+/// resolve to anything.
+pub fn resolve_ref<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    Pointer::from_uri_fragment(reference).ok()?.find(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::map;
+
+    #[test]
+    fn resolves_definition() {
+        let address = map(vec![("type", Value::String("object".into()))]);
+        let schema = map(vec![
+            ("definitions", map(vec![("Address", address.clone())])),
+            (
+                "properties",
+                map(vec![(
+                    "home",
+                    map(vec![(
+                        "$ref",
+                        Value::String("#/definitions/Address".into()),
+                    )]),
+                )]),
+            ),
+        ]);
+
+        assert_eq!(
+            Some(&address),
+            resolve_ref(&schema, "#/definitions/Address")
+        );
+        assert_eq!(
+            None,
+            resolve_ref(&schema, "other.json#/definitions/Address")
+        );
+    }
+}
@@ -0,0 +1,150 @@
+/*
+*   Copyright (c) 2019 Lukas Krejci
+*   All rights reserved.
+
+*   Licensed under the Apache License, Version 2.0 (the "License");
+*   you may not use this file except in compliance with the License.
+*   You may obtain a copy of the License at
+
+*   http://www.apache.org/licenses/LICENSE-2.0
+
+*   Unless required by applicable law or agreed to in writing, software
+*   distributed under the License is distributed on an "AS IS" BASIS,
+*   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*   See the License for the specific language governing permissions and
+*   limitations under the License.
+*/
+
+use crate::pointer::{Pointer, Step};
+use serde_value::Value;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::fmt;
+use std::fmt::Display;
+
+/// Describes why a value is not a valid step array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepArrayError {
+    /// The value is not a sequence.
+    NotASequence,
+
+    /// The element at the position is not a single-key map with a known step kind.
+    InvalidStep(usize),
+}
+
+pub(crate) fn to_step_array(pointer: &Pointer) -> Value {
+    Value::Seq(
+        pointer
+            .steps()
+            .iter()
+            .map(|step| {
+                let (kind, value) = match step {
+                    Step::Name(name) => ("name", Value::String(name.clone())),
+                    Step::Index(idx) => ("index", Value::U64(*idx as u64)),
+                    Step::NewElement => ("new", Value::Bool(true)),
+                    Step::Wildcard => ("wildcard", Value::Bool(true)),
+                    Step::IndexFromEnd(idx) => ("from_end", Value::U64(*idx as u64)),
+                };
+                let mut map = BTreeMap::new();
+                map.insert(Value::String(kind.to_owned()), value);
+                Value::Map(map)
+            })
+            .collect(),
+    )
+}
+
+pub(crate) fn from_step_array(val: &Value) -> Result<Pointer, StepArrayError> {
+    match val {
+        Value::Seq(seq) => seq
+            .iter()
+            .enumerate()
+            .map(|(pos, step)| _step(step).ok_or(StepArrayError::InvalidStep(pos)))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Pointer::from),
+        _ => Err(StepArrayError::NotASequence),
+    }
+}
+
+fn _step(val: &Value) -> Option<Step> {
+    let map = match val {
+        Value::Map(map) if map.len() == 1 => map,
+        _ => return None,
+    };
+    match map.iter().next()? {
+        (Value::String(kind), Value::String(name)) if kind == "name" => {
+            Some(Step::Name(name.clone()))
+        }
+        (Value::String(kind), idx) if kind == "index" => _index(idx).map(Step::Index),
+        (Value::String(kind), Value::Bool(true)) if kind == "new" => Some(Step::NewElement),
+        (Value::String(kind), Value::Bool(true)) if kind == "wildcard" => Some(Step::Wildcard),
+        (Value::String(kind), idx) if kind == "from_end" => _index(idx).map(Step::IndexFromEnd),
+        _ => None,
+    }
+}
+
+/// Accepts any integer type, so that the arrays deserialized from the other formats work too.
+fn _index(val: &Value) -> Option<usize> {
+    match *val {
+        Value::U8(i) => Some(i as usize),
+        Value::U16(i) => Some(i as usize),
+        Value::U32(i) => usize::try_from(i).ok(),
+        Value::U64(i) => usize::try_from(i).ok(),
+        Value::I8(i) => usize::try_from(i).ok(),
+        Value::I16(i) => usize::try_from(i).ok(),
+        Value::I32(i) => usize::try_from(i).ok(),
+        Value::I64(i) => usize::try_from(i).ok(),
+        _ => None,
+    }
+}
+
+impl Display for StepArrayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StepArrayError::NotASequence => f.write_str("The step array is not a sequence"),
+            StepArrayError::InvalidStep(pos) => f.write_fmt(format_args!(
+                "The element at position {} of the step array is not a valid step",
+                pos
+            )),
+        }
+    }
+}
+
+impl StdError for StepArrayError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut pointer = Pointer::default();
+        pointer
+            .push_name("a")
+            .push(Step::Index(0))
+            .push_name("0")
+            .push(Step::NewElement);
+
+        let array = to_step_array(&pointer);
+        match &array {
+            Value::Seq(seq) => assert_eq!(4, seq.len()),
+            _ => panic!("expected a sequence"),
+        }
+        assert_eq!(Ok(pointer), from_step_array(&array));
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            Err(StepArrayError::NotASequence),
+            from_step_array(&Value::String("/a".into()))
+        );
+
+        let mut bad = BTreeMap::new();
+        bad.insert(Value::String("index".into()), Value::I32(-1));
+        let mut name = BTreeMap::new();
+        name.insert(Value::String("name".into()), Value::String("a".into()));
+        let array = Value::Seq(vec![Value::Map(name), Value::Map(bad)]);
+        assert_eq!(Err(StepArrayError::InvalidStep(1)), from_step_array(&array));
+    }
+}
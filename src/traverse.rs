@@ -17,6 +17,12 @@
 
 use crate::pointer::{Pointer, Step};
 use serde_value::Value;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::fmt;
+use std::fmt::Display;
 
 /// A value pointer is what the traversal of the Serde pointer ends up at.
 pub enum ValuePointer<'a> {
@@ -35,6 +41,169 @@ pub enum ValuePointerMut<'a> {
     NewUnder(&'a mut Value, usize),
 }
 
+/// The error returned when making sure a path exists in a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnsureError {
+    /// The existing value cannot be traversed by the step at the given position. E.g. a name step
+    /// used on a sequence or any step used on a scalar.
+    TypeMismatch(usize),
+
+    /// The index step at the given position points past the end of the sequence. Only the gaps
+    /// that can be filled by appending to the sequence are filled.
+    IndexOutOfBounds(usize),
+}
+
+/// The error returned when resolving a pointer against a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolveError {
+    /// The step cannot be resolved against the value the `resolved` prefix of the pointer points
+    /// to.
+    NotFound { resolved: Pointer, step: Step },
+
+    /// The pointer ends with the new element step, which refers to a position to insert at rather
+    /// than to an existing value.
+    NewElementInRead,
+}
+
+pub(crate) fn find_checked<'a>(
+    val: &'a Value,
+    pointer: &Pointer,
+) -> Result<&'a Value, ResolveError> {
+    match pointer.steps().split_last() {
+        Some((Step::NewElement, parent)) => {
+            resolve(val, &parent.to_vec().into())?;
+            Err(ResolveError::NewElementInRead)
+        }
+        _ => resolve(val, pointer),
+    }
+}
+
+/// The outcome of finding a value considering all the keys and elements a step can match.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FindResult<'a> {
+    /// Exactly one value matches the pointer.
+    Unique(&'a Value),
+
+    /// Several distinct values match the pointer, in the order of the keys and elements.
+    Ambiguous(Vec<&'a Value>),
+
+    /// No value matches the pointer.
+    None,
+}
+
+/// Unlike `child()`, which picks the first of the matching keys, each step is followed into all
+/// the children it can match: the string key and the non-string keys of the same name in the maps
+/// and, for the numeric names, the elements of the sequences.
+pub(crate) fn find_ambiguous<'a>(val: &'a Value, pointer: &Pointer) -> FindResult<'a> {
+    let mut found = Vec::new();
+    _find_ambiguous(val, pointer.steps(), &mut found);
+    let mut distinct: Vec<&Value> = Vec::with_capacity(found.len());
+    for v in found {
+        if !distinct.iter().any(|d| std::ptr::eq(*d, v)) {
+            distinct.push(v);
+        }
+    }
+    match distinct.len() {
+        0 => FindResult::None,
+        1 => FindResult::Unique(distinct[0]),
+        _ => FindResult::Ambiguous(distinct),
+    }
+}
+
+fn _find_ambiguous<'a>(val: &'a Value, steps: &[Step], found: &mut Vec<&'a Value>) {
+    let (step, rest) = match steps.split_first() {
+        Some(split) => split,
+        None => return found.push(val),
+    };
+
+    let token = match step {
+        Step::Name(name) => Some(name.clone()),
+        Step::Index(idx) => Some(idx.to_string()),
+        _ => None,
+    };
+    let candidates: Vec<&Value> = match (token, unwrap_option(val)) {
+        (Some(token), Value::Map(map)) => std::iter::once(Value::String(token.clone()))
+            .chain(_non_string_keys(&token))
+            .filter_map(|k| map.get(&k))
+            .collect(),
+        (Some(token), Value::Seq(seq)) => match step {
+            Step::Name(_)
+                if !token.bytes().all(|b| b.is_ascii_digit())
+                    || (token.len() > 1 && token.starts_with('0')) =>
+            {
+                Vec::new()
+            }
+            _ => token
+                .parse::<usize>()
+                .ok()
+                .and_then(|i| seq.get(i))
+                .into_iter()
+                .collect(),
+        },
+        _ => child(val, &read_step(step, rest.is_empty()))
+            .into_iter()
+            .collect(),
+    };
+
+    for candidate in candidates {
+        _find_ambiguous(candidate, rest, found);
+    }
+}
+
+/// The error returned when a pointer is expected to point to a scalar value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalarError {
+    /// The pointer doesn't point to any value.
+    NotFound,
+
+    /// The pointer points to a map or a sequence.
+    IsContainer,
+}
+
+/// The error returned when a pointer refers past the end of a sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexValidationError {
+    /// The step at the position is an index that is not less than the length of the sequence it
+    /// is applied to.
+    OutOfRange {
+        position: usize,
+        index: usize,
+        len: usize,
+    },
+
+    /// The step at the position is an index from the end that is zero or greater than the length
+    /// of the sequence it is applied to.
+    OutOfRangeFromEnd {
+        position: usize,
+        index: usize,
+        len: usize,
+    },
+}
+
+/// The amount of work done while finding a value, as reported by `Pointer::find_profiled()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TraversalStats {
+    /// The number of values stepped into, not counting the root.
+    pub nodes_visited: usize,
+
+    /// The number of keys looked up in the maps, including the retries with the non-string keys.
+    pub key_lookups: usize,
+
+    /// The largest number of steps followed from the root.
+    pub max_depth: usize,
+}
+
+/// The error returned when several values cannot be borrowed mutably at the same time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlapError {
+    /// The pointers at the two positions point to the same value or one of the values contains
+    /// the other.
+    Overlapping(usize, usize),
+
+    /// The pointer at the position doesn't point to any value.
+    NotFound(usize),
+}
+
 pub(crate) fn traverse<'a>(val: &'a Value, pointer: &Pointer) -> Option<ValuePointer<'a>> {
     let mut it = pointer.clone().into_iter();
     match it.next() {
@@ -54,31 +223,173 @@ pub(crate) fn traverse_mut<'a>(
     }
 }
 
+pub(crate) fn resolve<'a>(val: &'a Value, pointer: &Pointer) -> Result<&'a Value, ResolveError> {
+    let steps = pointer.steps();
+    let mut current = val;
+    for (pos, step) in steps.iter().enumerate() {
+        let found = child(current, &read_step(step, pos + 1 == steps.len()));
+        current = found.ok_or_else(|| ResolveError::NotFound {
+            resolved: steps[..pos].to_vec().into(),
+            step: step.clone(),
+        })?;
+    }
+
+    Ok(current)
+}
+
+pub(crate) fn resolve_str<'a>(pointer: &str, val: &'a Value) -> Option<&'a Value> {
+    let pointer = pointer.strip_prefix('#').unwrap_or(pointer);
+    if pointer.is_empty() {
+        return Some(val);
+    }
+
+    let mut segments = pointer.strip_prefix('/')?.split('/').peekable();
+
+    // the single buffer reused for unescaping all the names
+    let mut name = String::new();
+    let mut current = val;
+    while let Some(segment) = segments.next() {
+        current = match (_streamed_index(segment), unwrap_option(current)) {
+            (Some(index), Value::Seq(seq)) => seq.get(index)?,
+            (Some(_), Value::Map(map)) => {
+                name.clear();
+                name.push_str(segment);
+                _map_child(map, &mut name)?
+            }
+            (Some(_), _) => return None,
+            // the new element never resolves to an existing value, but a map key "-" can be
+            // stepped through
+            (None, Value::Map(map)) if segment == "-" && segments.peek().is_some() => {
+                name.clear();
+                name.push('-');
+                _map_child(map, &mut name)?
+            }
+            (None, _) if segment == "-" => return None,
+            (None, Value::Map(map)) => {
+                _unescape_into(segment, &mut name)?;
+                _map_child(map, &mut name)?
+            }
+            (None, _) => return None,
+        };
+    }
+
+    Some(current)
+}
+
+/// Looks up the name in the map the same way `child()` does. The name is only moved into the
+/// string key for the lookup and handed back to the buffer afterwards.
+fn _map_child<'a>(map: &'a BTreeMap<Value, Value>, name: &mut String) -> Option<&'a Value> {
+    let key = Value::String(std::mem::take(name));
+    let found = map.get(&key);
+    if let Value::String(n) = key {
+        *name = n;
+    }
+    found.or_else(|| _non_string_keys(name).find_map(|k| map.get(&k)))
+}
+
+/// Recognizes the index segments the same way the parser does.
+fn _streamed_index(segment: &str) -> Option<usize> {
+    if segment == "0" {
+        return Some(0);
+    }
+
+    if segment.starts_with('0') || !segment.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    segment.parse::<usize>().ok()
+}
+
+fn _unescape_into(segment: &str, buf: &mut String) -> Option<()> {
+    buf.clear();
+    let mut chars = segment.chars();
+    while let Some(c) = chars.next() {
+        if c == '~' {
+            match chars.next() {
+                Some('0') => buf.push('~'),
+                Some('1') => buf.push('/'),
+                _ => return None,
+            }
+        } else {
+            buf.push(c);
+        }
+    }
+
+    Some(())
+}
+
+pub(crate) fn ensure_path<'a>(
+    val: &'a mut Value,
+    pointer: &Pointer,
+) -> Result<&'a mut Value, EnsureError> {
+    let steps: Vec<Step> = pointer.clone().into();
+    let mut current = val;
+    for (pos, step) in steps.iter().enumerate() {
+        let missing = || container_for(steps.get(pos + 1));
+        current = match (step, unwrap_option_mut(current)) {
+            (Step::Name(_), Value::Map(map)) | (Step::Index(_), Value::Map(map)) => {
+                let key = map_key(map, step).unwrap_or_else(|| new_key(step));
+                map.entry(key).or_insert_with(missing)
+            }
+            (Step::Index(index), Value::Seq(seq)) => {
+                if *index == seq.len() {
+                    seq.push(missing());
+                }
+                match seq.get_mut(*index) {
+                    Some(v) => v,
+                    None => return Err(EnsureError::IndexOutOfBounds(pos)),
+                }
+            }
+            (Step::NewElement, Value::Seq(seq)) => {
+                seq.push(missing());
+                seq.last_mut().unwrap()
+            }
+            _ => return Err(EnsureError::TypeMismatch(pos)),
+        };
+    }
+
+    Ok(current)
+}
+
+/// Creates an empty value that the provided step can traverse. A null value is created at the end
+/// of the path.
+pub(crate) fn container_for(step: Option<&Step>) -> Value {
+    match step {
+        Some(Step::Name(_)) => Value::Map(BTreeMap::new()),
+        Some(_) => Value::Seq(Vec::new()),
+        None => Value::Unit,
+    }
+}
+
+#[allow(clippy::manual_map)]
 fn _traverse_mut<'a>(
     parent: &'a mut Value,
     step: &Step,
     steps: &mut dyn Iterator<Item = Step>,
 ) -> Option<ValuePointerMut<'a>> {
+    let parent = unwrap_option_mut(parent);
     let child = match step {
-        Step::Name(name) => match parent {
-            Value::Map(ref mut map) => map.get_mut(&Value::String(name.clone())),
-            _ => None,
-        },
-        Step::Index(index) => match parent {
-            Value::Seq(ref mut seq) => seq.get_mut(*index),
-            _ => None,
-        },
         Step::NewElement => {
-            // avoid mutable borrow of parent by the ValuePointerMut while we have an immutable borrow in the match
-            // by splitting the code into separate "stages"
+            // avoid mutable borrow of parent by the ValuePointerMut while we have an immutable
+            // borrow in the match by splitting the code into separate "stages"
             let seq_len = match parent {
+                // it is only possible to reference a new element by the last step
                 Value::Seq(seq) => {
-                    if let Some(_) = steps.next() {
+                    if steps.next().is_none() {
                         Some(seq.len())
                     } else {
                         None
                     }
                 }
+                Value::Map(_) => {
+                    return match steps.next() {
+                        Some(child_step) => {
+                            let dash = read_step(step, false);
+                            _traverse_mut(child_mut(parent, &dash)?, &child_step, steps)
+                        }
+                        None => None,
+                    };
+                }
                 _ => None,
             };
 
@@ -88,11 +399,12 @@ fn _traverse_mut<'a>(
                 None
             };
         }
+        _ => child_mut(parent, step),
     };
 
     match steps.next() {
-        Some(child_step) => _traverse_mut(child.unwrap(), &child_step, steps),
-        None => Some(ValuePointerMut::Existing(child.unwrap())),
+        Some(child_step) => _traverse_mut(child?, &child_step, steps),
+        None => Some(ValuePointerMut::Existing(child?)),
     }
 }
 
@@ -101,15 +413,8 @@ fn _traverse<'a>(
     step: &Step,
     steps: &mut dyn Iterator<Item = Step>,
 ) -> Option<ValuePointer<'a>> {
+    let parent = unwrap_option(parent);
     let child = match step {
-        Step::Name(name) => match parent {
-            Value::Map(ref map) => map.get(&Value::String(name.clone())),
-            _ => None,
-        },
-        Step::Index(index) => match parent {
-            Value::Seq(ref seq) => seq.get(*index),
-            _ => None,
-        },
         Step::NewElement => {
             return match parent {
                 Value::Seq(ref seq) => match steps.next() {
@@ -117,118 +422,1585 @@ fn _traverse<'a>(
                     None => Some(ValuePointer::NewUnder(parent, seq.len())),
                     _ => None,
                 },
+                Value::Map(_) => match steps.next() {
+                    Some(child_step) => {
+                        _traverse(child(parent, &read_step(step, false))?, &child_step, steps)
+                    }
+                    None => None,
+                },
                 _ => None,
-            }
+            };
         }
+        _ => child(parent, step),
     };
 
     match steps.next() {
-        Some(child_step) => _traverse(child.unwrap(), &child_step, steps),
-        None => Some(ValuePointer::Existing(child.unwrap())),
+        Some(child_step) => _traverse(child?, &child_step, steps),
+        None => Some(ValuePointer::Existing(child?)),
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::collections::BTreeMap;
-    use std::mem;
-    use std::str::FromStr;
+/// Returns the step the traversals reading the data follow instead of the provided one. The new
+/// element step anywhere but at the end of the pointer is looked up as the `"-"` key, so that
+/// `/-/x` reaches into such a key of a map. As the last step, it keeps referring to a new element.
+pub(crate) fn read_step(step: &Step, last: bool) -> Cow<'_, Step> {
+    match step {
+        Step::NewElement if !last => Cow::Owned(Step::Name("-".to_owned())),
+        _ => Cow::Borrowed(step),
+    }
+}
 
-    #[test]
-    fn finds_all_data() {
-        let val = Value::Map(BTreeMap::default());
-        assert_eq!(&val, Pointer::from_str("#").unwrap().find(&val).unwrap());
+/// Looks up the child of the parent the name or index step points to.
+///
+/// The names are looked up as the string keys in the maps first. If there is no such key, the name
+/// is tried as a bool key (for `true` and `false`) or an integer key. The index steps are looked up
+/// the same way as the numeric names.
+pub(crate) fn child<'a>(parent: &'a Value, step: &Step) -> Option<&'a Value> {
+    match (step, unwrap_option(parent)) {
+        (Step::Name(name), Value::Map(map)) => map
+            .get(&Value::String(name.clone()))
+            .or_else(|| _non_string_keys(name).find_map(|k| map.get(&k))),
+        (Step::Index(index), Value::Map(map)) => {
+            let name = index.to_string();
+            map.get(&Value::String(name.clone()))
+                .or_else(|| _non_string_keys(&name).find_map(|k| map.get(&k)))
+        }
+        (Step::Index(index), Value::Seq(seq)) => seq.get(*index),
+        (Step::IndexFromEnd(index), Value::Seq(seq)) => seq.get(seq.len().checked_sub(*index)?),
+        _ => None,
     }
+}
 
-    #[test]
-    fn finds_concrete_data() {
-        let val = Value::Map(
-            vec![
-                (Value::String("k1".into()), Value::String("v1".into())),
-                (
-                    Value::String("k2".into()),
-                    Value::Seq(vec![Value::I32(42), Value::Bool(true)]),
-                ),
-            ]
-            .into_iter()
-            .collect(),
-        );
+pub(crate) fn child_mut<'a>(parent: &'a mut Value, step: &Step) -> Option<&'a mut Value> {
+    match (step, unwrap_option_mut(parent)) {
+        (Step::Index(index), Value::Seq(seq)) => seq.get_mut(*index),
+        (Step::IndexFromEnd(index), Value::Seq(seq)) => {
+            let index = seq.len().checked_sub(*index)?;
+            seq.get_mut(index)
+        }
+        (_, Value::Map(map)) => {
+            let key = map_key(map, step)?;
+            map.get_mut(&key)
+        }
+        _ => None,
+    }
+}
 
-        assert_eq!(
-            Value::Bool(true),
-            *Pointer::from_str("/k2/1").unwrap().find(&val).unwrap()
-        );
+/// Looks through the optional values that are present, so that the steps apply to the actual
+/// values. The absent optional values are returned as they are and no step applies to them.
+pub(crate) fn unwrap_option(val: &Value) -> &Value {
+    match val {
+        Value::Option(Some(inner)) => unwrap_option(inner),
+        _ => val,
     }
+}
 
-    #[test]
-    fn find_new_element() {
-        let val = Value::Map(
-            vec![
-                (Value::String("k1".into()), Value::String("v1".into())),
-                (
-                    Value::String("k2".into()),
-                    Value::Seq(vec![Value::Bool(true)]),
-                ),
-            ]
-            .into_iter()
+pub(crate) fn unwrap_option_mut(val: &mut Value) -> &mut Value {
+    match val {
+        Value::Option(Some(inner)) => unwrap_option_mut(inner),
+        _ => val,
+    }
+}
+
+/// A stable label of the type of the value. The present optional values are labeled by the type of
+/// the value inside them, the absent ones are `"null"`, same as the unit value.
+pub(crate) fn type_name(val: &Value) -> &'static str {
+    match unwrap_option(val) {
+        Value::Bool(_) => "bool",
+        Value::U8(_) => "u8",
+        Value::U16(_) => "u16",
+        Value::U32(_) => "u32",
+        Value::U64(_) => "u64",
+        Value::I8(_) => "i8",
+        Value::I16(_) => "i16",
+        Value::I32(_) => "i32",
+        Value::I64(_) => "i64",
+        Value::F32(_) => "f32",
+        Value::F64(_) => "f64",
+        Value::Char(_) => "char",
+        Value::String(_) => "string",
+        Value::Unit | Value::Option(_) => "null",
+        Value::Newtype(_) => "newtype",
+        Value::Seq(_) => "seq",
+        Value::Map(_) => "map",
+        Value::Bytes(_) => "bytes",
+    }
+}
+
+/// Finds the key of the map entry the name or index step points to, using the same fallbacks as
+/// `child()`.
+pub(crate) fn map_key(map: &BTreeMap<Value, Value>, step: &Step) -> Option<Value> {
+    _key_candidates(step)
+        .into_iter()
+        .find(|k| map.contains_key(k))
+}
+
+/// The string key a name or an index step creates in a map that has none of the keys it can match.
+pub(crate) fn new_key(step: &Step) -> Value {
+    match step {
+        Step::Index(index) => Value::String(index.to_string()),
+        Step::Name(name) => Value::String(name.clone()),
+        _ => unreachable!("only the names and indices address the map keys"),
+    }
+}
+
+/// The keys a name or an index step can match in a map, in the order they are tried - the string
+/// key first, then the bool and integer keys.
+fn _key_candidates(step: &Step) -> Vec<Value> {
+    let name = match step {
+        Step::Name(name) => name.clone(),
+        Step::Index(index) => index.to_string(),
+        _ => return Vec::new(),
+    };
+    let mut keys = vec![Value::String(name.clone())];
+    keys.extend(_non_string_keys(&name));
+    keys
+}
+
+/// Returns the steps to the direct children of the value, in the key order for maps and the index
+/// order for sequences.
+pub(crate) fn child_steps(val: &Value) -> Vec<Step> {
+    match unwrap_option(val) {
+        Value::Map(map) => map
+            .keys()
+            .filter_map(|k| key_name(map, k))
+            .map(Step::Name)
             .collect(),
-        );
+        Value::Seq(seq) => (0..seq.len()).map(Step::Index).collect(),
+        _ => Vec::new(),
+    }
+}
 
-        let found = Pointer::from_str("/k2/-").unwrap().traverse(&val).unwrap();
-        match found {
-            ValuePointer::NewUnder(parent, idx) => {
-                assert_eq!(1, idx);
-                match parent {
-                    Value::Seq(ar) => {
-                        assert_eq!(1, ar.len());
-                    }
-                    _ => panic!("Should have found an array of size 1"),
+/// Calls the function for every scalar (i.e. not a map or a sequence) value in the tree, in the key
+/// order for maps and the index order for sequences, together with the steps leading to it. With
+/// `empty_containers`, the empty maps and sequences are passed to the function, too.
+pub(crate) fn walk_leaves<'a, F: FnMut(&[Step], &'a Value)>(
+    val: &'a Value,
+    empty_containers: bool,
+    path: &mut Vec<Step>,
+    f: &mut F,
+) {
+    match unwrap_option(val) {
+        Value::Map(map) if empty_containers && map.is_empty() => f(path, val),
+        Value::Seq(seq) if empty_containers && seq.is_empty() => f(path, val),
+        Value::Map(map) => {
+            for (k, v) in map {
+                if let Some(name) = key_name(map, k) {
+                    path.push(Step::Name(name));
+                    walk_leaves(v, empty_containers, path, f);
+                    path.pop();
                 }
             }
-            _ => panic!("Should have found value pointer to a new value."),
         }
+        Value::Seq(seq) => {
+            for (i, v) in seq.iter().enumerate() {
+                path.push(Step::Index(i));
+                walk_leaves(v, empty_containers, path, f);
+                path.pop();
+            }
+        }
+        _ => f(path, val),
     }
+}
 
-    #[test]
-    fn can_mutate() {
-        let mut val = Value::Map(
-            vec![
-                (Value::String("k1".into()), Value::String("v1".into())),
-                (
-                    Value::String("k2".into()),
-                    Value::Seq(vec![Value::Bool(true)]),
-                ),
-            ]
-            .into_iter()
-            .collect(),
-        );
+/// Instead of comparing the pointers, which could say two different pointers are disjoint when
+/// they resolve to the same key (e.g. an index and a numeric name), the values are descended into
+/// together. At each value, the pointers are grouped by the concrete child they continue to and
+/// each child is borrowed only once. Two pointers overlap when one of them ends at a value another
+/// one is still at.
+pub(crate) fn find_many_mut<'a>(
+    val: &'a mut Value,
+    pointers: &[Pointer],
+) -> Result<Vec<&'a mut Value>, OverlapError> {
+    if pointers.is_empty() {
+        return Ok(Vec::new());
+    }
 
-        let found = Pointer::from_str("/k2/0")
-            .unwrap()
-            .traverse_mut(&mut val)
-            .unwrap();
-            
-        match found {
-            ValuePointerMut::Existing(v) => {
-                mem::replace(v, Value::String("boo".into()));
+    let mut found = Vec::with_capacity(pointers.len());
+    found.resize_with(pointers.len(), || None);
+    let items = pointers
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (i, p.steps()))
+        .collect();
+    _find_many_mut(val, items, &mut found)?;
+    Ok(found.into_iter().map(|v| v.unwrap()).collect())
+}
+
+fn _find_many_mut<'a>(
+    val: &'a mut Value,
+    items: Vec<(usize, &[Step])>,
+    found: &mut [Option<&'a mut Value>],
+) -> Result<(), OverlapError> {
+    if let Some((ended, _)) = items.iter().find(|(_, steps)| steps.is_empty()) {
+        return match items.iter().find(|(i, _)| i != ended) {
+            Some((other, _)) => Err(OverlapError::Overlapping(
+                *ended.min(other),
+                *ended.max(other),
+            )),
+            None => {
+                found[*ended] = Some(val);
+                Ok(())
             }
-            _ => {}
-        }
+        };
+    }
 
-        match val {
-            Value::Map(map) => {
-                match map.get(&Value::String("k2".into())).unwrap() {
-                    Value::Seq(seq) => match seq.get(0).unwrap() {
-                        Value::String(s) => {
-                            assert_eq!(s.as_str(), "boo");
-                        }
-                        _ => panic!("Should have found a string"),
-                    },
-                    _ => panic!("Should have found a seq"),
+    match unwrap_option_mut(val) {
+        Value::Map(map) => {
+            let mut groups: BTreeMap<Value, Vec<(usize, &[Step])>> = BTreeMap::new();
+            for (i, steps) in items {
+                let step = read_step(&steps[0], steps.len() == 1);
+                let key = map_key(map, &step).ok_or(OverlapError::NotFound(i))?;
+                groups.entry(key).or_default().push((i, &steps[1..]));
+            }
+            for (key, child) in map.iter_mut() {
+                if let Some(items) = groups.remove(key) {
+                    _find_many_mut(child, items, found)?;
+                }
+            }
+            Ok(())
+        }
+        Value::Seq(seq) => {
+            let mut groups: BTreeMap<usize, Vec<(usize, &[Step])>> = BTreeMap::new();
+            for (i, steps) in items {
+                let index = match steps[0] {
+                    Step::Index(idx) if idx < seq.len() => idx,
+                    Step::IndexFromEnd(idx) if idx >= 1 && idx <= seq.len() => seq.len() - idx,
+                    _ => return Err(OverlapError::NotFound(i)),
                 };
+                groups.entry(index).or_default().push((i, &steps[1..]));
+            }
+            for (index, child) in seq.iter_mut().enumerate() {
+                if let Some(items) = groups.remove(&index) {
+                    _find_many_mut(child, items, found)?;
+                }
+            }
+            Ok(())
+        }
+        _ => Err(OverlapError::NotFound(items[0].0)),
+    }
+}
+
+pub(crate) fn normalized(val: &Value, pointer: &Pointer) -> Pointer {
+    _normalized(val, pointer, true)
+}
+
+pub(crate) fn canonicalize_indices(val: &Value, pointer: &Pointer) -> Pointer {
+    _normalized(val, pointer, false)
+}
+
+/// Once a step doesn't resolve, the rest of the steps are left as they are. The indices used on
+/// maps are only turned into names if `index_names` is set.
+fn _normalized(val: &Value, pointer: &Pointer, index_names: bool) -> Pointer {
+    let mut current = Some(val);
+    let len = pointer.steps().len();
+    let steps = pointer
+        .steps()
+        .iter()
+        .enumerate()
+        .map(|(pos, step)| {
+            let step = match (step, current.map(unwrap_option)) {
+                (Step::Name(name), Some(Value::Seq(_))) => match name.parse::<usize>() {
+                    Ok(idx) if idx.to_string() == *name => Step::Index(idx),
+                    _ => step.clone(),
+                },
+                (Step::Index(idx), Some(Value::Map(_))) if index_names => {
+                    Step::Name(idx.to_string())
+                }
+                _ => step.clone(),
+            };
+            current = current.and_then(|c| child(c, &read_step(&step, pos + 1 == len)));
+            step
+        })
+        .collect::<Vec<_>>();
+    Pointer::from(steps)
+}
+
+/// The exact key is preferred, otherwise the first of the matching string keys in the key order
+/// is used.
+pub(crate) fn find_case_insensitive<'a>(val: &'a Value, pointer: &Pointer) -> Option<&'a Value> {
+    let mut current = val;
+    let steps = pointer.steps();
+    for (pos, step) in steps.iter().enumerate() {
+        let step = read_step(step, pos + 1 == steps.len());
+        current = match (&*step, unwrap_option(current)) {
+            (Step::Name(name), Value::Map(map)) => child(current, &step).or_else(|| {
+                map.iter().find_map(|(k, v)| match k {
+                    Value::String(key) if _names_match(key, name) => Some(v),
+                    _ => None,
+                })
+            })?,
+            _ => child(current, &step)?,
+        };
+    }
+    Some(current)
+}
+
+/// The aliases are not chained, an aliased name that misses is not looked up in the aliases again.
+pub(crate) fn find_with_aliases<'a>(
+    val: &'a Value,
+    pointer: &Pointer,
+    aliases: &HashMap<String, String>,
+) -> Option<&'a Value> {
+    let mut current = val;
+    let steps = pointer.steps();
+    for (pos, step) in steps.iter().enumerate() {
+        let step = read_step(step, pos + 1 == steps.len());
+        current = match (child(current, &step), &*step) {
+            (Some(found), _) => found,
+            (None, Step::Name(name)) => child(current, &Step::Name(aliases.get(name)?.clone()))?,
+            (None, _) => return None,
+        };
+    }
+    Some(current)
+}
+
+#[cfg(not(feature = "unicode-case"))]
+fn _names_match(a: &str, b: &str) -> bool {
+    a.eq_ignore_ascii_case(b)
+}
+
+#[cfg(feature = "unicode-case")]
+fn _names_match(a: &str, b: &str) -> bool {
+    unicase::UniCase::new(a) == unicase::UniCase::new(b)
+}
+
+/// Searches the tree for the target by its address. Returns `true` with the path to the target
+/// left in `path` if found.
+pub(crate) fn path_of(val: &Value, target: &Value, path: &mut Vec<Step>) -> bool {
+    if std::ptr::eq(val, target) {
+        return true;
+    }
+
+    let mut descend = |child: &Value, step: Step| {
+        path.push(step);
+        let found = path_of(child, target, path);
+        if !found {
+            path.pop();
+        }
+        found
+    };
+
+    match val {
+        Value::Option(Some(inner)) => path_of(inner, target, path),
+        Value::Map(map) => map
+            .iter()
+            .any(|(k, v)| key_name(map, k).is_some_and(|name| descend(v, Step::Name(name)))),
+        Value::Seq(seq) => seq
+            .iter()
+            .enumerate()
+            .any(|(i, v)| descend(v, Step::Index(i))),
+        _ => false,
+    }
+}
+
+/// Collects the paths to all the map entries with the key, depth-first, in the key order for maps
+/// and the index order for sequences. The values of the matching entries are searched, too.
+pub(crate) fn find_key_anywhere(
+    val: &Value,
+    key: &str,
+    path: &mut Vec<Step>,
+    found: &mut Vec<Pointer>,
+) {
+    match unwrap_option(val) {
+        Value::Map(map) => {
+            for (k, v) in map {
+                if let Some(name) = key_name(map, k) {
+                    let matches = name == key;
+                    path.push(Step::Name(name));
+                    if matches {
+                        found.push(Pointer::from(path.clone()));
+                    }
+                    find_key_anywhere(v, key, path, found);
+                    path.pop();
+                }
             }
-            _ => panic!("Should see the map"),
         }
+        Value::Seq(seq) => {
+            for (i, v) in seq.iter().enumerate() {
+                path.push(Step::Index(i));
+                find_key_anywhere(v, key, path, found);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Returns the name under which the key of the map can be looked up. Only the string keys and the
+/// bool and integer keys supported by the lookup fallback can, and the latter only if no other key
+/// of the map takes precedence for the same name, e.g. the string key `"5"` over the `5` key.
+pub(crate) fn key_name(map: &BTreeMap<Value, Value>, key: &Value) -> Option<String> {
+    let name = _key_string(key)?;
+    match key {
+        Value::String(_) => Some(name),
+        _ if map_key(map, &Step::Name(name.clone())).as_ref() == Some(key) => Some(name),
+        _ => None,
+    }
+}
+
+fn _key_string(key: &Value) -> Option<String> {
+    match key {
+        Value::String(s) => Some(s.clone()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::I8(i) => Some(i.to_string()),
+        Value::I16(i) => Some(i.to_string()),
+        Value::I32(i) => Some(i.to_string()),
+        Value::I64(i) => Some(i.to_string()),
+        Value::U8(i) => Some(i.to_string()),
+        Value::U16(i) => Some(i.to_string()),
+        Value::U32(i) => Some(i.to_string()),
+        Value::U64(i) => Some(i.to_string()),
+        _ => None,
+    }
+}
+
+/// The non-string map keys the name can represent. The bool keys are tried for `true` and `false`,
+/// otherwise the signed integer keys from the widest are tried followed by the unsigned ones.
+fn _non_string_keys(name: &str) -> impl Iterator<Item = Value> {
+    let boolean = match name {
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ => None,
+    };
+    let signed = name.parse::<i64>().ok().into_iter().flat_map(|i| {
+        IntoIterator::into_iter([
+            Some(Value::I64(i)),
+            i32::try_from(i).ok().map(Value::I32),
+            i16::try_from(i).ok().map(Value::I16),
+            i8::try_from(i).ok().map(Value::I8),
+        ])
+        .flatten()
+    });
+    let unsigned = name.parse::<u64>().ok().into_iter().flat_map(|u| {
+        IntoIterator::into_iter([
+            Some(Value::U64(u)),
+            u32::try_from(u).ok().map(Value::U32),
+            u16::try_from(u).ok().map(Value::U16),
+            u8::try_from(u).ok().map(Value::U8),
+        ])
+        .flatten()
+    });
+    boolean.into_iter().chain(signed).chain(unsigned)
+}
+
+pub(crate) fn find_resolved<'a>(
+    val: &'a Value,
+    steps: &[Step],
+    resolved: &mut Vec<Step>,
+) -> Option<&'a Value> {
+    let (step, rest) = match steps.split_first() {
+        Some(split) => split,
+        None => return Some(val),
+    };
+
+    match (step, unwrap_option(val)) {
+        (Step::Wildcard, Value::Map(map)) => map.iter().find_map(|(k, v)| {
+            _find_resolved_child(v, Step::Name(key_name(map, k)?), rest, resolved)
+        }),
+        (Step::Wildcard, Value::Seq(seq)) => seq
+            .iter()
+            .enumerate()
+            .find_map(|(i, v)| _find_resolved_child(v, Step::Index(i), rest, resolved)),
+        _ => {
+            let (found, step) = _resolved_child(val, step, rest.is_empty())?;
+            _find_resolved_child(found, step, rest, resolved)
+        }
+    }
+}
+
+pub(crate) fn find_profiled<'a>(
+    val: &'a Value,
+    pointer: &Pointer,
+) -> (Option<&'a Value>, TraversalStats) {
+    let mut stats = TraversalStats::default();
+    let found = _find_profiled(val, pointer.steps(), 0, &mut stats);
+    (found, stats)
+}
+
+fn _find_profiled<'a>(
+    val: &'a Value,
+    steps: &[Step],
+    depth: usize,
+    stats: &mut TraversalStats,
+) -> Option<&'a Value> {
+    stats.max_depth = stats.max_depth.max(depth);
+    let (step, rest) = match steps.split_first() {
+        Some(split) => split,
+        None => return Some(val),
+    };
+
+    let visit = |child: &'a Value, stats: &mut TraversalStats| {
+        stats.nodes_visited += 1;
+        _find_profiled(child, rest, depth + 1, stats)
+    };
+
+    match (step, unwrap_option(val)) {
+        (Step::Wildcard, Value::Map(map)) => map
+            .iter()
+            .filter(|(k, _)| key_name(map, k).is_some())
+            .find_map(|(_, v)| visit(v, stats)),
+        (Step::Wildcard, Value::Seq(seq)) => seq.iter().find_map(|v| visit(v, stats)),
+        _ => visit(
+            _profiled_child(val, &read_step(step, rest.is_empty()), stats)?,
+            stats,
+        ),
+    }
+}
+
+fn _profiled_child<'a>(
+    parent: &'a Value,
+    step: &Step,
+    stats: &mut TraversalStats,
+) -> Option<&'a Value> {
+    match (step, unwrap_option(parent)) {
+        (Step::Name(_), Value::Map(map)) | (Step::Index(_), Value::Map(map)) => {
+            _key_candidates(step).iter().find_map(|k| {
+                stats.key_lookups += 1;
+                map.get(k)
+            })
+        }
+        _ => child(parent, step),
+    }
+}
+
+/// Finds the child like `child()` and returns it with the step that matched it: a name of the key
+/// for the maps and an index for the sequences.
+fn _resolved_child<'a>(val: &'a Value, step: &Step, last: bool) -> Option<(&'a Value, Step)> {
+    let step = read_step(step, last);
+    match (&*step, unwrap_option(val)) {
+        (Step::Name(_), Value::Map(map)) | (Step::Index(_), Value::Map(map)) => {
+            let key = map_key(map, &step)?;
+            Some((map.get(&key)?, Step::Name(key_name(map, &key)?)))
+        }
+        (Step::Index(index), Value::Seq(seq)) => Some((seq.get(*index)?, Step::Index(*index))),
+        (Step::IndexFromEnd(index), Value::Seq(seq)) => {
+            let index = seq.len().checked_sub(*index)?;
+            Some((seq.get(index)?, Step::Index(index)))
+        }
+        _ => None,
+    }
+}
+
+/// Like `find_resolved()` but doesn't track the concrete steps taken.
+pub(crate) fn find_first<'a>(val: &'a Value, steps: &[Step]) -> Option<&'a Value> {
+    let (step, rest) = match steps.split_first() {
+        Some(split) => split,
+        None => return Some(val),
+    };
+
+    match (step, unwrap_option(val)) {
+        (Step::Wildcard, Value::Map(map)) => map
+            .iter()
+            .filter(|(k, _)| key_name(map, k).is_some())
+            .find_map(|(_, v)| find_first(v, rest)),
+        (Step::Wildcard, Value::Seq(seq)) => seq.iter().find_map(|v| find_first(v, rest)),
+        _ => find_first(child(val, &read_step(step, rest.is_empty()))?, rest),
+    }
+}
+
+/// Like `find_resolved()` but expands the wildcards to all the children under which the rest of
+/// the steps resolve.
+pub(crate) fn find_all_resolved<'a>(
+    val: &'a Value,
+    steps: &[Step],
+    resolved: &mut Vec<Step>,
+    found: &mut Vec<(Pointer, &'a Value)>,
+) {
+    let (step, rest) = match steps.split_first() {
+        Some(split) => split,
+        None => return found.push((Pointer::from(resolved.clone()), val)),
+    };
+
+    let mut descend = |child: &'a Value, step: Step| {
+        resolved.push(step);
+        find_all_resolved(child, rest, resolved, found);
+        resolved.pop();
+    };
+
+    match (step, unwrap_option(val)) {
+        (Step::Wildcard, Value::Map(map)) => {
+            for (k, v) in map {
+                if let Some(name) = key_name(map, k) {
+                    descend(v, Step::Name(name));
+                }
+            }
+        }
+        (Step::Wildcard, Value::Seq(seq)) => {
+            for (i, v) in seq.iter().enumerate() {
+                descend(v, Step::Index(i));
+            }
+        }
+        _ => {
+            if let Some((child, step)) = _resolved_child(val, step, rest.is_empty()) {
+                descend(child, step);
+            }
+        }
+    }
+}
+
+fn _find_resolved_child<'a>(
+    child: &'a Value,
+    step: Step,
+    rest: &[Step],
+    resolved: &mut Vec<Step>,
+) -> Option<&'a Value> {
+    resolved.push(step);
+    let found = find_resolved(child, rest, resolved);
+    if found.is_none() {
+        resolved.pop();
+    }
+    found
+}
+
+impl Display for EnsureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnsureError::TypeMismatch(pos) => f.write_fmt(format_args!(
+                "Cannot create path: step at position {} doesn't match the type of the value",
+                pos
+            )),
+            EnsureError::IndexOutOfBounds(pos) => f.write_fmt(format_args!(
+                "Cannot create path: index at position {} is out of bounds",
+                pos
+            )),
+        }
+    }
+}
+
+impl StdError for EnsureError {}
+
+impl Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolveError::NotFound { resolved, step } => {
+                let at = if resolved.is_root() {
+                    "the root".to_owned()
+                } else {
+                    resolved.to_string()
+                };
+                match step {
+                    Step::Name(name) => f.write_fmt(format_args!(
+                        "Value not found: found up to {}, but it has no key `{}`",
+                        at, name
+                    )),
+                    Step::Index(index) => f.write_fmt(format_args!(
+                        "Value not found: found up to {}, but it has no index {}",
+                        at, index
+                    )),
+                    Step::IndexFromEnd(index) => f.write_fmt(format_args!(
+                        "Value not found: found up to {}, but it has no index {} from the end",
+                        at, index
+                    )),
+                    Step::NewElement | Step::Wildcard => f.write_fmt(format_args!(
+                        "Value not found: found up to {}, but `{}` doesn't resolve to a single value",
+                        at, step
+                    )),
+                }
+            }
+            ResolveError::NewElementInRead => f.write_str(
+                "The new element step (`-`) at the end of the pointer refers to a position to \
+                 insert at, not to an existing value",
+            ),
+        }
+    }
+}
+
+impl StdError for ResolveError {}
+
+impl Display for OverlapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverlapError::Overlapping(a, b) => f.write_fmt(format_args!(
+                "The values of the pointers at positions {} and {} overlap",
+                a, b
+            )),
+            OverlapError::NotFound(pos) => f.write_fmt(format_args!(
+                "The pointer at position {} doesn't point to any value",
+                pos
+            )),
+        }
+    }
+}
+
+impl StdError for OverlapError {}
+
+impl Display for ScalarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScalarError::NotFound => f.write_str("Value not found"),
+            ScalarError::IsContainer => f.write_str("Expected a scalar value, found a container"),
+        }
+    }
+}
+
+impl StdError for ScalarError {}
+
+pub(crate) fn find_scalar<'a>(val: &'a Value, pointer: &Pointer) -> Result<&'a Value, ScalarError> {
+    let found = pointer.find(val).ok_or(ScalarError::NotFound)?;
+    match unwrap_option(found) {
+        Value::Map(_) | Value::Seq(_) => Err(ScalarError::IsContainer),
+        _ => Ok(found),
+    }
+}
+
+pub(crate) fn validate_indices(val: &Value, pointer: &Pointer) -> Result<(), IndexValidationError> {
+    let steps = pointer.steps();
+    let mut current = val;
+    for (position, step) in steps.iter().enumerate() {
+        match (step, unwrap_option(current)) {
+            (Step::Index(index), Value::Seq(seq)) if *index >= seq.len() => {
+                return Err(IndexValidationError::OutOfRange {
+                    position,
+                    index: *index,
+                    len: seq.len(),
+                });
+            }
+            (Step::IndexFromEnd(index), Value::Seq(seq)) if *index == 0 || *index > seq.len() => {
+                return Err(IndexValidationError::OutOfRangeFromEnd {
+                    position,
+                    index: *index,
+                    len: seq.len(),
+                });
+            }
+            _ => {}
+        }
+        current = match child(current, &read_step(step, position + 1 == steps.len())) {
+            Some(child) => child,
+            None => break,
+        };
+    }
+    Ok(())
+}
+
+impl Display for IndexValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexValidationError::OutOfRange {
+                position,
+                index,
+                len,
+            } => f.write_fmt(format_args!(
+                "The index {} at position {} is out of range of a sequence of length {}",
+                index, position, len
+            )),
+            IndexValidationError::OutOfRangeFromEnd {
+                position,
+                index,
+                len,
+            } => f.write_fmt(format_args!(
+                "The index -{} at position {} is out of range of a sequence of length {}",
+                index, position, len
+            )),
+        }
+    }
+}
+
+impl StdError for IndexValidationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem;
+    use std::str::FromStr;
+
+    #[test]
+    fn finds_all_data() {
+        let val = Value::Map(BTreeMap::default());
+        assert_eq!(&val, Pointer::from_str("#").unwrap().find(&val).unwrap());
+    }
+
+    #[test]
+    fn finds_concrete_data() {
+        let val = Value::Map(
+            vec![
+                (Value::String("k1".into()), Value::String("v1".into())),
+                (
+                    Value::String("k2".into()),
+                    Value::Seq(vec![Value::I32(42), Value::Bool(true)]),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        assert_eq!(
+            Value::Bool(true),
+            *Pointer::from_str("/k2/1").unwrap().find(&val).unwrap()
+        );
+    }
+
+    #[test]
+    fn find_new_element() {
+        let val = Value::Map(
+            vec![
+                (Value::String("k1".into()), Value::String("v1".into())),
+                (
+                    Value::String("k2".into()),
+                    Value::Seq(vec![Value::Bool(true)]),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        let found = Pointer::from_str("/k2/-").unwrap().traverse(&val).unwrap();
+        match found {
+            ValuePointer::NewUnder(parent, idx) => {
+                assert_eq!(1, idx);
+                match parent {
+                    Value::Seq(ar) => {
+                        assert_eq!(1, ar.len());
+                    }
+                    _ => panic!("Should have found an array of size 1"),
+                }
+            }
+            _ => panic!("Should have found value pointer to a new value."),
+        }
+    }
+
+    #[test]
+    #[allow(unused_must_use, clippy::single_match, clippy::get_first)]
+    fn can_mutate() {
+        let mut val = Value::Map(
+            vec![
+                (Value::String("k1".into()), Value::String("v1".into())),
+                (
+                    Value::String("k2".into()),
+                    Value::Seq(vec![Value::Bool(true)]),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        let found = Pointer::from_str("/k2/0")
+            .unwrap()
+            .traverse_mut(&mut val)
+            .unwrap();
+
+        match found {
+            ValuePointerMut::Existing(v) => {
+                mem::replace(v, Value::String("boo".into()));
+            }
+            _ => {}
+        }
+
+        match val {
+            Value::Map(map) => {
+                match map.get(&Value::String("k2".into())).unwrap() {
+                    Value::Seq(seq) => match seq.get(0).unwrap() {
+                        Value::String(s) => {
+                            assert_eq!(s.as_str(), "boo");
+                        }
+                        _ => panic!("Should have found a string"),
+                    },
+                    _ => panic!("Should have found a seq"),
+                };
+            }
+            _ => panic!("Should see the map"),
+        }
+    }
+
+    #[test]
+    fn ensures_path_in_empty_map() {
+        let mut val = Value::Map(BTreeMap::default());
+
+        let leaf = Pointer::from_str("/a/b/0/c")
+            .unwrap()
+            .ensure_path(&mut val)
+            .unwrap();
+        assert_eq!(Value::Unit, *leaf);
+        *leaf = Value::Bool(true);
+
+        let expected = Value::Map(
+            vec![(
+                Value::String("a".into()),
+                Value::Map(
+                    vec![(
+                        Value::String("b".into()),
+                        Value::Seq(vec![Value::Map(
+                            vec![(Value::String("c".into()), Value::Bool(true))]
+                                .into_iter()
+                                .collect(),
+                        )]),
+                    )]
+                    .into_iter()
+                    .collect(),
+                ),
+            )]
+            .into_iter()
+            .collect(),
+        );
+        assert_eq!(expected, val);
+    }
+
+    #[test]
+    fn ensure_path_looks_through_options() {
+        let mut val = Value::Map(
+            vec![(
+                Value::String("o".into()),
+                Value::Option(Some(Box::new(Value::Map(BTreeMap::new())))),
+            )]
+            .into_iter()
+            .collect(),
+        );
+
+        let p = Pointer::from_str("/o/x").unwrap();
+        *p.ensure_path(&mut val).unwrap() = Value::Bool(true);
+        assert_eq!(Some(&Value::Bool(true)), p.find(&val));
+    }
+
+    #[test]
+    fn ensure_path_reuses_matching_keys() {
+        let mut val = Value::Map(
+            vec![
+                (Value::U8(5), Value::Map(BTreeMap::new())),
+                (Value::String("0".into()), Value::Map(BTreeMap::new())),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        for p in &["/5/x", "/0/x", "/7"] {
+            let p = Pointer::from_str(p).unwrap();
+            *p.ensure_path(&mut val).unwrap() = Value::Bool(true);
+            assert_eq!(Some(&Value::Bool(true)), p.find(&val));
+        }
+        match &val {
+            Value::Map(map) => {
+                assert_eq!(3, map.len());
+                assert!(map.contains_key(&Value::String("7".into())));
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn ensure_path_fails_on_type_mismatch() {
+        let mut val = Value::Map(
+            vec![(Value::String("a".into()), Value::String("v1".into()))]
+                .into_iter()
+                .collect(),
+        );
+
+        let res = Pointer::from_str("/a/b").unwrap().ensure_path(&mut val);
+        assert_eq!(Err(EnsureError::TypeMismatch(1)), res);
+    }
+
+    #[test]
+    fn resolve_str_matches_find() {
+        let val = Value::Map(
+            vec![
+                (Value::String("k1".into()), Value::String("v1".into())),
+                (
+                    Value::String("k~/2".into()),
+                    Value::Seq(vec![Value::I32(42), Value::Bool(true)]),
+                ),
+                (Value::String("007".into()), Value::Seq(vec![Value::I32(7)])),
+                (Value::String("12ab".into()), Value::I32(12)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        for p in &[
+            "",
+            "#",
+            "/k1",
+            "#/k1",
+            "/k~0~12/1",
+            "/k~0~12/2",
+            "/k~0~12/-",
+            "/007/0",
+            "/k1/x",
+            "/k~2",
+            "/k~",
+            "/12ab",
+            "/12ab~",
+            "/~",
+            "k1",
+            "/",
+        ] {
+            let expected = Pointer::from_str(p).ok().and_then(|ptr| ptr.find(&val));
+            assert_eq!(expected, resolve_str(p, &val), "pointer {}", p);
+        }
+    }
+
+    #[test]
+    fn finds_under_non_string_keys() {
+        let mut val = Value::Map(
+            vec![
+                (Value::Bool(true), Value::String("yes".into())),
+                (Value::I64(-1), Value::String("minus one".into())),
+                (Value::U8(5), Value::String("five".into())),
+                (
+                    Value::String("5".into()),
+                    Value::String("string five".into()),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let find = |val: &Value, s| Pointer::from_str(s).unwrap().find(val).cloned();
+
+        assert_eq!(Some(Value::String("yes".into())), find(&val, "/true"));
+        assert_eq!(Some(Value::String("minus one".into())), find(&val, "/-1"));
+        assert_eq!(Some(Value::String("string five".into())), find(&val, "/5"));
+        assert_eq!(None, find(&val, "/false"));
+        assert_eq!(find(&val, "/true").as_ref(), resolve_str("/true", &val));
+        assert_eq!(find(&val, "/5").as_ref(), resolve_str("/5", &val));
+
+        *Pointer::from_str("/true")
+            .unwrap()
+            .find_mut(&mut val)
+            .unwrap() = Value::Unit;
+        assert_eq!(Some(Value::Unit), find(&val, "/true"));
+
+        val = Value::Map(
+            vec![(Value::U8(5), Value::String("five".into()))]
+                .into_iter()
+                .collect(),
+        );
+        assert_eq!(Some(Value::String("five".into())), find(&val, "/5"));
+        assert_eq!(find(&val, "/5").as_ref(), resolve_str("/5", &val));
+    }
+
+    #[test]
+    fn skips_shadowed_non_string_keys() {
+        let val = Value::Map(
+            vec![
+                (Value::U8(5), Value::I32(1)),
+                (Value::String("5".into()), Value::I32(2)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let five = match &val {
+            Value::Map(map) => map.get(&Value::U8(5)).unwrap(),
+            _ => unreachable!(),
+        };
+
+        let named = Pointer::from(vec![Step::Name("5".into())]);
+        assert_eq!(None, Pointer::of(&val, five));
+        assert_eq!(vec![named.clone()], Pointer::leaves(&val));
+        assert_eq!(vec![named], Pointer::find_key_anywhere(&val, "5"));
+        assert_eq!(vec![Step::Name("5".into())], child_steps(&val));
+    }
+
+    #[test]
+    fn prefers_string_keys() {
+        let val = Value::Map(
+            vec![
+                (Value::Bool(true), Value::String("bool".into())),
+                (Value::String("true".into()), Value::String("string".into())),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        assert_eq!(
+            Some(&Value::String("string".into())),
+            Pointer::from_str("/true").unwrap().find(&val)
+        );
+    }
+
+    #[test]
+    fn finds_resolved_wildcard() {
+        let val = Value::Map(
+            vec![(
+                Value::String("items".into()),
+                Value::Seq(vec![Value::I32(1), Value::I32(2)]),
+            )]
+            .into_iter()
+            .collect(),
+        );
+        let p = Pointer::from(vec![Step::Name("items".into()), Step::Wildcard]);
+
+        let (found, resolved) = p.find_resolved(&val).unwrap();
+        assert_eq!(Value::I32(1), *found);
+        assert_eq!(Pointer::from_str("/items/0").unwrap(), resolved);
+    }
+
+    #[test]
+    fn validates_indices() {
+        let items = Value::Seq(vec![Value::Seq(vec![Value::I32(1)]), Value::Unit]);
+        let val = Value::Map(
+            vec![(Value::String("items".into()), items)]
+                .into_iter()
+                .collect(),
+        );
+        let validate = |p| Pointer::from_str(p).unwrap().validate_indices(&val);
+
+        assert_eq!(Ok(()), validate("/items/1"));
+        assert_eq!(Ok(()), validate("/items/0/0"));
+        assert_eq!(Ok(()), validate("/missing/5"));
+        assert_eq!(
+            Err(IndexValidationError::OutOfRange {
+                position: 2,
+                index: 1,
+                len: 1
+            }),
+            validate("/items/0/1")
+        );
+        assert_eq!(
+            Err(IndexValidationError::OutOfRange {
+                position: 1,
+                index: 2,
+                len: 2
+            }),
+            validate("/items/2/0")
+        );
+
+        let from_end = |idx| {
+            Pointer::from(vec![Step::Name("items".into()), Step::IndexFromEnd(idx)])
+                .validate_indices(&val)
+        };
+        assert_eq!(Ok(()), from_end(2));
+        for idx in [0, 3] {
+            assert_eq!(
+                Err(IndexValidationError::OutOfRangeFromEnd {
+                    position: 1,
+                    index: idx,
+                    len: 2
+                }),
+                from_end(idx)
+            );
+        }
+    }
+
+    #[test]
+    fn profiles_find() {
+        let c = Value::Map(
+            vec![(Value::String("c".into()), Value::Bool(true))]
+                .into_iter()
+                .collect(),
+        );
+        let b = Value::Map(vec![(Value::String("b".into()), c)].into_iter().collect());
+        let val = Value::Map(vec![(Value::String("a".into()), b)].into_iter().collect());
+
+        let (found, stats) = Pointer::from_str("/a/b/c").unwrap().find_profiled(&val);
+        assert_eq!(Some(&Value::Bool(true)), found);
+        assert_eq!(
+            TraversalStats {
+                nodes_visited: 3,
+                key_lookups: 3,
+                max_depth: 3
+            },
+            stats
+        );
+
+        let (found, stats) = Pointer::from_str("/a/x/c").unwrap().find_profiled(&val);
+        assert_eq!(None, found);
+        assert_eq!(1, stats.nodes_visited);
+        assert_eq!(1, stats.max_depth);
+    }
+
+    #[test]
+    fn finds_first_wildcard_match() {
+        let items = Value::Seq(vec![Value::I32(1), Value::I32(2)]);
+        let val = Value::Map(
+            vec![(Value::String("items".into()), items)]
+                .into_iter()
+                .collect(),
+        );
+        let p = Pointer::from(vec![Step::Name("items".into()), Step::Wildcard]);
+        assert_eq!(Some(&Value::I32(1)), p.find_first(&val));
+
+        let p = Pointer::from(vec![Step::Wildcard, Step::Name("a".into())]);
+        let val = Value::Seq(vec![
+            Value::Map(BTreeMap::default()),
+            Value::Map(
+                vec![(Value::String("a".into()), Value::Bool(true))]
+                    .into_iter()
+                    .collect(),
+            ),
+        ]);
+        assert_eq!(Some(&Value::Bool(true)), p.find_first(&val));
+        assert_eq!(None, p.find_first(&Value::Seq(vec![])));
+    }
+
+    #[test]
+    fn resolves_to_matched_steps() {
+        let val = Value::Map(
+            vec![
+                (Value::String("0".into()), Value::I32(1)),
+                (Value::U8(1), Value::Seq(vec![Value::I32(2), Value::I32(3)])),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let resolved = |p: Pointer| p.find_resolved(&val).map(|(_, r)| r.steps().to_vec());
+
+        assert_eq!(
+            Some(vec![Step::Name("0".into())]),
+            resolved(Pointer::from_str("/0").unwrap())
+        );
+        assert_eq!(
+            Some(vec![Step::Name("1".into()), Step::Index(1)]),
+            resolved(Pointer::from(vec![Step::Index(1), Step::IndexFromEnd(1)]))
+        );
+
+        let mut named = Pointer::default();
+        named.push_name("0");
+        let all = Pointer::from_str("/0").unwrap().find_all_resolved(&val);
+        assert_eq!(vec![(named, &Value::I32(1))], all);
+    }
+
+    #[test]
+    fn finds_resolved_wildcard_skipping_unresolvable() {
+        let val = Value::Seq(vec![
+            Value::Map(BTreeMap::default()),
+            Value::Map(
+                vec![(Value::String("a".into()), Value::Bool(true))]
+                    .into_iter()
+                    .collect(),
+            ),
+        ]);
+        let p = Pointer::from(vec![Step::Wildcard, Step::Name("a".into())]);
+
+        let (found, resolved) = p.find_resolved(&val).unwrap();
+        assert_eq!(Value::Bool(true), *found);
+        assert_eq!(Pointer::from_str("/1/a").unwrap(), resolved);
+    }
+
+    #[test]
+    fn finds_all_resolved() {
+        let item = |id: i32| {
+            Value::Map(
+                vec![(Value::String("id".into()), Value::I32(id))]
+                    .into_iter()
+                    .collect(),
+            )
+        };
+        let val = Value::Map(
+            vec![(
+                Value::String("items".into()),
+                Value::Seq(vec![item(7), Value::Unit, item(8)]),
+            )]
+            .into_iter()
+            .collect(),
+        );
+        let p = Pointer::from(vec![
+            Step::Name("items".into()),
+            Step::Wildcard,
+            Step::Name("id".into()),
+        ]);
+
+        let found = p.find_all_resolved(&val);
+        assert_eq!(
+            vec![
+                (Pointer::from_str("/items/0/id").unwrap(), &Value::I32(7)),
+                (Pointer::from_str("/items/2/id").unwrap(), &Value::I32(8)),
+            ],
+            found
+        );
+    }
+
+    #[test]
+    fn finds_scalar() {
+        let val = Value::Map(
+            vec![
+                (Value::String("a".into()), Value::Seq(vec![Value::I32(1)])),
+                (Value::String("b".into()), Value::Bool(false)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let find = |p: &str| Pointer::from_str(p).unwrap().find_scalar(&val);
+
+        assert_eq!(Ok(&Value::Bool(false)), find("/b"));
+        assert_eq!(Ok(&Value::I32(1)), find("/a/0"));
+        assert_eq!(Err(ScalarError::IsContainer), find("/a"));
+        assert_eq!(Err(ScalarError::IsContainer), find(""));
+        assert_eq!(Err(ScalarError::NotFound), find("/c"));
+    }
+
+    #[test]
+    fn steps_through_dash_key() {
+        let mut val = Value::Map(
+            vec![(
+                Value::String("-".into()),
+                Value::Map(
+                    vec![(Value::String("x".into()), Value::I32(1))]
+                        .into_iter()
+                        .collect(),
+                ),
+            )]
+            .into_iter()
+            .collect(),
+        );
+
+        let p = Pointer::from_str("/-/x").unwrap();
+        assert_eq!(Some(&Value::I32(1)), p.find(&val));
+        assert_eq!(Ok(&Value::I32(1)), p.resolve(&val));
+        assert_eq!(Some(&Value::I32(1)), resolve_str("/-/x", &val));
+        assert!(p.find_mut(&mut val).is_some());
+
+        assert_eq!(Some(&Value::I32(1)), p.find_first(&val));
+        assert_eq!(Some(&Value::I32(1)), p.find_case_insensitive(&val));
+        assert_eq!(
+            Some(&Value::I32(1)),
+            p.find_with_aliases(&val, &HashMap::new())
+        );
+        assert_eq!(Some(&Value::I32(1)), p.find_lazy(&val));
+        // the concrete pointers name the key that matched
+        let mut named = Pointer::default();
+        named.push_name("-").push_name("x");
+        assert_eq!(Some((&Value::I32(1), named.clone())), p.find_resolved(&val));
+        assert_eq!(vec![(named, &Value::I32(1))], p.find_all_resolved(&val));
+        assert_eq!(FindResult::Unique(&Value::I32(1)), p.find_ambiguous(&val));
+        assert_eq!(
+            Some(&Value::I32(1)),
+            p.iter_resolved(&val).last().map(|(_, v)| v)
+        );
+        assert_eq!((p.clone(), &Value::I32(1)), p.deepest_match(&val));
+
+        // the last new element step still doesn't point to an existing value
+        assert_eq!(None, Pointer::from_str("/-").unwrap().find(&val));
+        assert_eq!(None, resolve_str("/-", &val));
+    }
+
+    #[test]
+    fn finds_case_insensitive() {
+        let val = Value::Map(
+            vec![
+                (Value::String("Content-Type".into()), Value::I32(1)),
+                (Value::String("content-type".into()), Value::I32(2)),
+                (Value::String("Straße".into()), Value::I32(3)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let find = |p: &str| Pointer::from_str(p).unwrap().find_case_insensitive(&val);
+
+        assert_eq!(Some(&Value::I32(2)), find("/content-type"));
+        assert_eq!(Some(&Value::I32(1)), find("/CONTENT-TYPE"));
+        assert_eq!(Some(&Value::I32(3)), find("/STRAßE"));
+        assert_eq!(None, find("/accept"));
+        if cfg!(feature = "unicode-case") {
+            assert_eq!(Some(&Value::I32(3)), find("/STRASSE"));
+        } else {
+            assert_eq!(None, find("/STRASSE"));
+        }
+    }
+
+    #[test]
+    fn finds_many_mut() {
+        let mut val = Value::Map(
+            vec![
+                (
+                    Value::String("a".into()),
+                    Value::Seq(vec![Value::I32(1), Value::I32(2)]),
+                ),
+                (Value::I32(7), Value::Bool(false)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let pointers = |ps: &[&str]| {
+            ps.iter()
+                .map(|p| Pointer::from_str(p).unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        let mut found = find_many_mut(&mut val, &pointers(&["/a/1", "/7", "/a/0"])).unwrap();
+        *found[0] = Value::I32(20);
+        *found[1] = Value::Bool(true);
+        *found[2] = Value::I32(10);
+        assert_eq!(
+            Some(&Value::Seq(vec![Value::I32(10), Value::I32(20)])),
+            Pointer::from_str("/a").unwrap().find(&val)
+        );
+        assert_eq!(
+            Some(&Value::Bool(true)),
+            Pointer::from_str("/7").unwrap().find(&val)
+        );
+
+        let mut overlap = |ps: &[&str]| find_many_mut(&mut val, &pointers(ps)).map(|_| ());
+        assert_eq!(
+            Err(OverlapError::Overlapping(0, 1)),
+            overlap(&["/a", "/a/0"])
+        );
+        assert_eq!(
+            Err(OverlapError::Overlapping(1, 2)),
+            overlap(&["/7", "/a/1", "/a/1"])
+        );
+
+        assert_eq!(Err(OverlapError::NotFound(1)), overlap(&["/a/0", "/a/5"]));
+
+        // the same key addressed by a name and by an index
+        let mut named = Pointer::default();
+        named.push_name("7");
+        let indexed = Pointer::from(vec![Step::Index(7)]);
+        assert_eq!(
+            Err(OverlapError::Overlapping(0, 1)),
+            find_many_mut(&mut val, &[named, indexed]).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn finds_many_mut_without_pointers() {
+        let mut val = Value::Bool(true);
+        assert!(find_many_mut(&mut val, &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn finds_checked() {
+        let val = Value::Map(
+            vec![(Value::String("arr".into()), Value::Seq(vec![Value::I32(1)]))]
+                .into_iter()
+                .collect(),
+        );
+        let find = |p: &str| Pointer::from_str(p).unwrap().find_checked(&val);
+
+        assert_eq!(Err(ResolveError::NewElementInRead), find("/arr/-"));
+        assert_eq!(None, Pointer::from_str("/arr/-").unwrap().find(&val));
+        assert_eq!(Ok(&Value::I32(1)), find("/arr/0"));
+        assert!(matches!(find("/x/-"), Err(ResolveError::NotFound { .. })));
+    }
+
+    #[test]
+    fn finds_ambiguous() {
+        let val = Value::Map(
+            vec![
+                (
+                    Value::String("0".into()),
+                    Value::Map(
+                        vec![(Value::String("0".into()), Value::Bool(true))]
+                            .into_iter()
+                            .collect(),
+                    ),
+                ),
+                (Value::U8(0), Value::Seq(vec![Value::Bool(false)])),
+                (Value::String("a".into()), Value::Seq(vec![Value::Unit])),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let find = |p: &str| Pointer::from_str(p).unwrap().find_ambiguous(&val);
+
+        // `/0` is both the string key and the integer key, `/0/0` thus goes to both a map key and
+        // a sequence element
+        assert_eq!(
+            FindResult::Ambiguous(vec![&Value::Bool(true), &Value::Bool(false)]),
+            find("/0/0")
+        );
+        assert_eq!(FindResult::Unique(&Value::Unit), find("/a/0"));
+        assert_eq!(FindResult::None, find("/a/1"));
+    }
+
+    #[test]
+    fn finds_with_aliases() {
+        let val = Value::Map(
+            vec![
+                (Value::String("color".into()), Value::I32(1)),
+                (Value::String("size".into()), Value::I32(2)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let aliases = vec![("colour", "color"), ("hue", "colour"), ("size", "length")]
+            .into_iter()
+            .map(|(a, b)| (a.to_owned(), b.to_owned()))
+            .collect::<HashMap<_, _>>();
+        let find = |p: &str| {
+            Pointer::from_str(p)
+                .unwrap()
+                .find_with_aliases(&val, &aliases)
+        };
+
+        assert_eq!(Some(&Value::I32(1)), find("/colour"));
+        assert_eq!(Some(&Value::I32(1)), find("/color"));
+        assert_eq!(Some(&Value::I32(2)), find("/size"));
+        assert_eq!(None, find("/hue"));
+        assert_eq!(None, find("/weight"));
+    }
+
+    #[test]
+    fn traverses_mut_to_new_element() {
+        let mut val = Value::Seq(vec![Value::Bool(true)]);
+
+        let found = Pointer::from_str("/-").unwrap().traverse_mut(&mut val);
+        assert!(matches!(found, Some(ValuePointerMut::NewUnder(_, 1))));
+
+        let found = Pointer::from_str("/-/a").unwrap().traverse_mut(&mut val);
+        assert!(found.is_none());
+    }
+
+    #[test]
+    fn traverses_through_options() {
+        let mut val = Value::Map(
+            vec![
+                (
+                    Value::String("opt".into()),
+                    Value::Option(Some(Box::new(Value::Map(
+                        vec![(Value::String("a".into()), Value::I32(1))]
+                            .into_iter()
+                            .collect(),
+                    )))),
+                ),
+                (Value::String("none".into()), Value::Option(None)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        assert_eq!(
+            Some(&Value::I32(1)),
+            Pointer::from_str("/opt/a").unwrap().find(&val)
+        );
+        assert_eq!(Some(&Value::I32(1)), resolve_str("/opt/a", &val));
+        assert_eq!(None, Pointer::from_str("/none/a").unwrap().find(&val));
+
+        *Pointer::from_str("/opt/a")
+            .unwrap()
+            .find_mut(&mut val)
+            .unwrap() = Value::I32(2);
+        assert_eq!(
+            Some(&Value::I32(2)),
+            Pointer::from_str("/opt/a").unwrap().find(&val)
+        );
+        assert!(Pointer::from_str("/none/a")
+            .unwrap()
+            .find_mut(&mut val)
+            .is_none());
+    }
+
+    #[test]
+    fn resolve_reports_resolved_prefix() {
+        let val = Value::Map(
+            vec![(
+                Value::String("a".into()),
+                Value::Map(
+                    vec![(Value::String("b".into()), Value::Map(BTreeMap::new()))]
+                        .into_iter()
+                        .collect(),
+                ),
+            )]
+            .into_iter()
+            .collect(),
+        );
+
+        let err = Pointer::from_str("/a/b/c/d")
+            .unwrap()
+            .resolve(&val)
+            .unwrap_err();
+
+        assert_eq!(
+            ResolveError::NotFound {
+                resolved: Pointer::from_str("/a/b").unwrap(),
+                step: Step::Name("c".into())
+            },
+            err
+        );
+        assert_eq!(
+            "Value not found: found up to /a/b, but it has no key `c`",
+            err.to_string()
+        );
+        assert_eq!(
+            Ok(&Value::Map(BTreeMap::new())),
+            Pointer::from_str("/a/b").unwrap().resolve(&val)
+        );
     }
 }
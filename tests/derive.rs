@@ -0,0 +1,64 @@
+#![cfg(feature = "derive")]
+
+use serde::Serialize;
+use serde_pointer::{pointer_for, PointerFields};
+use serde_value::to_value;
+
+#[derive(Serialize, PointerFields)]
+struct Outer {
+    #[serde(rename = "innerValue")]
+    inner: Inner,
+    name: String,
+}
+
+#[derive(Serialize, PointerFields)]
+struct Inner {
+    #[serde(rename = "x-count")]
+    count: u32,
+    #[serde(skip)]
+    _cache: u32,
+}
+
+#[derive(Serialize, PointerFields)]
+struct Raw {
+    r#type: String,
+}
+
+#[test]
+fn pointer_for_uses_serde_names() {
+    assert_eq!("/name", pointer_for!(Outer, name).to_string());
+    assert_eq!("/innerValue", pointer_for!(Outer, inner).to_string());
+    assert_eq!(
+        "/innerValue/x-count",
+        pointer_for!(Outer, inner.count).to_string()
+    );
+
+    let value = to_value(Outer {
+        inner: Inner {
+            count: 42,
+            _cache: 0,
+        },
+        name: "n".into(),
+    })
+    .unwrap();
+    assert_eq!(
+        Some(&serde_value::Value::U32(42)),
+        pointer_for!(Outer, inner.count).find(&value)
+    );
+}
+
+#[test]
+fn pointer_for_accepts_raw_identifiers() {
+    assert_eq!("/type", pointer_for!(Raw, r#type).to_string());
+
+    let value = to_value(Raw { r#type: "t".into() }).unwrap();
+    assert_eq!(
+        Some(&serde_value::Value::String("t".into())),
+        pointer_for!(Raw, r#type).find(&value)
+    );
+}
+
+#[test]
+fn typo_fails_to_compile() {
+    trybuild::TestCases::new().compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,131 @@
+/*
+*   Copyright (c) 2019 Lukas Krejci
+*   All rights reserved.
+
+*   Licensed under the Apache License, Version 2.0 (the "License");
+*   you may not use this file except in compliance with the License.
+*   You may obtain a copy of the License at
+
+*   http://www.apache.org/licenses/LICENSE-2.0
+
+*   Unless required by applicable law or agreed to in writing, software
+*   distributed under the License is distributed on an "AS IS" BASIS,
+*   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*   See the License for the specific language governing permissions and
+*   limitations under the License.
+*/
+
+//! Checks that rendering any pointer and parsing it back gives the original pointer.
+
+use serde_pointer::{Pointer, Step};
+use std::str::FromStr;
+
+/// A small deterministic xorshift generator so that the failures are reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+const NAME_CHARS: &[char] = &[
+    'a', 'z', '0', '1', '9', '~', '/', '-', '#', '%', ' ', 'é', '€',
+];
+
+fn gen_name(rng: &mut Rng) -> String {
+    loop {
+        let len = rng.below(6);
+        let name: String = (0..len)
+            .map(|_| NAME_CHARS[rng.below(NAME_CHARS.len() as u64) as usize])
+            .collect();
+
+        // the names that look like an index or the new element are parsed as such, which is
+        // the documented ambiguity of the string form
+        if !looks_like_index_or_new_element(&name) {
+            return name;
+        }
+    }
+}
+
+fn looks_like_index_or_new_element(name: &str) -> bool {
+    name == "-"
+        || name == "0"
+        || (!name.starts_with('0')
+            && !name.is_empty()
+            && name.chars().all(|c| c.is_ascii_digit())
+            && name.parse::<usize>().is_ok())
+}
+
+fn gen_step(rng: &mut Rng) -> Step {
+    match rng.below(4) {
+        0 | 1 => Step::Name(gen_name(rng)),
+        2 => Step::Index(match rng.below(3) {
+            0 => rng.below(10) as usize,
+            1 => rng.next() as usize,
+            _ => usize::MAX,
+        }),
+        _ => Step::NewElement,
+    }
+}
+
+fn gen_pointer(rng: &mut Rng) -> Pointer {
+    let len = rng.below(6);
+    (0..len).map(|_| gen_step(rng)).collect::<Vec<_>>().into()
+}
+
+#[test]
+fn display_round_trips() {
+    let mut rng = Rng(0x5eed_1234_abcd_0001);
+    for _ in 0..10_000 {
+        let pointer = gen_pointer(&mut rng);
+        let rendered = pointer.to_string();
+        let parsed = Pointer::from_str(&rendered).unwrap_or_else(|e| {
+            panic!(
+                "Failed to parse {:?} rendered from {:?}: {}",
+                rendered, pointer, e
+            )
+        });
+        assert_eq!(pointer, parsed, "rendered as {:?}", rendered);
+    }
+}
+
+#[test]
+fn uri_fragment_round_trips() {
+    let mut rng = Rng(0x5eed_1234_abcd_0002);
+    for _ in 0..10_000 {
+        let pointer = gen_pointer(&mut rng);
+        let fragment = pointer.to_uri_fragment();
+        let parsed = Pointer::from_uri_fragment(&fragment).unwrap_or_else(|e| {
+            panic!(
+                "Failed to parse {:?} rendered from {:?}: {}",
+                fragment, pointer, e
+            )
+        });
+        assert_eq!(pointer, parsed, "rendered as {:?}", fragment);
+    }
+}
+
+#[test]
+fn subtle_cases_round_trip() {
+    for steps in [
+        vec![Step::Name(String::new())],
+        vec![Step::Name(String::new()), Step::Name(String::new())],
+        vec![Step::Name("007".to_owned())],
+        vec![Step::Name("00".to_owned())],
+        vec![Step::Name("-1".to_owned())],
+        vec![Step::Name("~1".to_owned())],
+        vec![Step::Name("18446744073709551616".to_owned())],
+        vec![Step::Index(usize::MAX)],
+    ] {
+        let pointer = Pointer::from(steps);
+        assert_eq!(pointer, Pointer::from_str(&pointer.to_string()).unwrap());
+    }
+}
@@ -0,0 +1,16 @@
+use serde::Serialize;
+use serde_pointer::{pointer_for, PointerFields};
+
+#[derive(Serialize, PointerFields)]
+struct Outer {
+    inner: Inner,
+}
+
+#[derive(Serialize, PointerFields)]
+struct Inner {
+    count: u32,
+}
+
+fn main() {
+    let _ = pointer_for!(Outer, inner.cuont);
+}